@@ -39,22 +39,16 @@ fn main() {
 							println!("Could not receive message: {:?}", err);
 							stream.send(OwnedMessage::Close(None)).map(|s| (None, s))
 						})
-						.and_then(|(msg, stream)| -> Box<Future<Item = _, Error = _>> {
+						.and_then(|(msg, stream)| -> Box<dyn Future<Item = _, Error = _>> {
 							match msg {
 								Some(OwnedMessage::Text(txt)) => Box::new(
-									stream
-										.send(OwnedMessage::Text(txt))
-										.map(|s| Loop::Continue(s)),
+									stream.send(OwnedMessage::Text(txt)).map(Loop::Continue),
 								),
 								Some(OwnedMessage::Binary(bin)) => Box::new(
-									stream
-										.send(OwnedMessage::Binary(bin))
-										.map(|s| Loop::Continue(s)),
+									stream.send(OwnedMessage::Binary(bin)).map(Loop::Continue),
 								),
 								Some(OwnedMessage::Ping(data)) => Box::new(
-									stream
-										.send(OwnedMessage::Pong(data))
-										.map(|s| Loop::Continue(s)),
+									stream.send(OwnedMessage::Pong(data)).map(Loop::Continue),
 								),
 								Some(OwnedMessage::Close(_)) => Box::new(
 									stream
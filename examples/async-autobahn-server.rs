@@ -7,10 +7,11 @@ use websocket::message::OwnedMessage;
 use websocket::server::InvalidConnection;
 
 use futures::{Future, Sink, Stream};
+use tokio::reactor::Handle;
 
 fn main() {
 	let mut runtime = tokio::runtime::Builder::new().build().unwrap();
-	let reactor = runtime.reactor().clone();
+	let reactor = Handle::default();
 	let executor = runtime.executor();
 	// bind to the server
 	let server = Server::bind("127.0.0.1:9002", &reactor).unwrap();
@@ -11,7 +11,7 @@ use std::thread;
 use websocket::result::WebSocketError;
 use websocket::{ClientBuilder, OwnedMessage};
 
-const CONNECTION: &'static str = "ws://127.0.0.1:2794";
+const CONNECTION: &str = "ws://127.0.0.1:2794";
 
 fn main() {
 	println!("Connecting to {}", CONNECTION);
@@ -63,6 +63,7 @@ fn main() {
 				})
 				.select(stdin_ch.map_err(|_| WebSocketError::NoDataAvailable))
 				.forward(sink)
+				.map(|_| ())
 		});
 	runtime.block_on(runner).unwrap();
 }
@@ -9,11 +9,12 @@ use websocket::message::{Message, OwnedMessage};
 use websocket::server::InvalidConnection;
 
 use futures::{Future, Sink, Stream};
+use tokio::reactor::Handle;
 use tokio::runtime::TaskExecutor;
 
 fn main() {
 	let mut runtime = tokio::runtime::Builder::new().build().unwrap();
-	let reactor = runtime.reactor().clone();
+	let reactor = Handle::default();
 	let executor = runtime.executor();
 	// bind to the server
 	let server = Server::bind("127.0.0.1:2794", &reactor).unwrap();
@@ -7,7 +7,7 @@ use std::thread;
 use websocket::client::ClientBuilder;
 use websocket::{Message, OwnedMessage};
 
-const CONNECTION: &'static str = "ws://127.0.0.1:8081";
+const CONNECTION: &str = "ws://127.0.0.1:8081";
 
 fn main() {
 	println!("Connecting to {}", CONNECTION);
@@ -36,13 +36,10 @@ fn main() {
 					return;
 				}
 			};
-			match message {
-				OwnedMessage::Close(_) => {
-					let _ = sender.send_message(&message);
-					// If it's a close message, just send it and then return.
-					return;
-				}
-				_ => (),
+			if let OwnedMessage::Close(_) = message {
+				let _ = sender.send_message(&message);
+				// If it's a close message, just send it and then return.
+				return;
 			}
 			// Send the message
 			match sender.send_message(&message) {
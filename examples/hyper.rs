@@ -10,7 +10,7 @@ use std::thread;
 use websocket::sync::Server;
 use websocket::{Message, OwnedMessage};
 
-const HTML: &'static str = include_str!("websockets.html");
+const HTML: &str = include_str!("websockets.html");
 
 // The HTTP server handler
 fn http_handler(_: Request, response: Response<Fresh>) {
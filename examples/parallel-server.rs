@@ -6,6 +6,7 @@ use websocket::async::Server;
 use websocket::message::OwnedMessage;
 use websocket::server::InvalidConnection;
 
+use tokio::reactor::Handle;
 use tokio::runtime;
 use tokio::runtime::TaskExecutor;
 
@@ -24,7 +25,7 @@ fn main() {
 	let runtime = runtime::Builder::new().build().unwrap();
 	let executor = runtime.executor();
 	let server =
-		Server::bind("127.0.0.1:8081", &runtime.reactor()).expect("Failed to create server");
+		Server::bind("127.0.0.1:8081", &Handle::default()).expect("Failed to create server");
 	let connections = Arc::new(RwLock::new(HashMap::new()));
 	let (receive_channel_out, receive_channel_in) = mpsc::unbounded();
 	let conn_id = Arc::new(RwLock::new(Counter::new()));
@@ -173,7 +174,7 @@ impl Iterator for Counter {
 	type Item = Id;
 
 	fn next(&mut self) -> Option<Id> {
-		if self.count != Id::max_value() {
+		if self.count != Id::MAX {
 			self.count += 1;
 			Some(self.count)
 		} else {
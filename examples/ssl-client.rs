@@ -11,7 +11,7 @@ use std::thread;
 use websocket::result::WebSocketError;
 use websocket::{ClientBuilder, OwnedMessage};
 
-const CONNECTION: &'static str = "wss://echo.websocket.org";
+const CONNECTION: &str = "wss://echo.websocket.org";
 
 fn main() {
 	println!("Connecting to {}", CONNECTION);
@@ -62,6 +62,7 @@ fn main() {
 				})
 				.select(stdin_ch.map_err(|_| WebSocketError::NoDataAvailable))
 				.forward(sink)
+				.map(|_| ())
 		});
 	runtime.block_on(runner).unwrap();
 }
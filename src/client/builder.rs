@@ -5,6 +5,8 @@ use header::{Origin, WebSocketExtensions, WebSocketKey, WebSocketProtocol, WebSo
 use hyper::header::{Header, HeaderFormat, Headers};
 use hyper::version::HttpVersion;
 use std::borrow::Cow;
+use std::net::SocketAddr;
+use std::time::Duration;
 pub use url::{ParseError, Url};
 
 #[cfg(any(feature = "sync", feature = "async"))]
@@ -30,6 +32,27 @@ use self::common_imports::*;
 
 #[cfg(feature = "sync")]
 use super::sync::Client;
+#[cfg(feature = "sync")]
+use hyper::header::ContentLength;
+#[cfg(feature = "sync")]
+use socket2::{Domain, Socket, Type};
+#[cfg(feature = "sync")]
+use std::cmp;
+#[cfg(feature = "sync")]
+use std::io::{self, Read};
+#[cfg(feature = "sync")]
+use std::net::Shutdown;
+#[cfg(feature = "sync")]
+use std::sync::mpsc;
+#[cfg(feature = "sync")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "sync")]
+use std::thread;
+#[cfg(feature = "sync")]
+use std::time::Instant;
+
+#[cfg(all(feature = "sync", feature = "deflate"))]
+use deflate::{self, DeflateConfig};
 
 #[cfg(feature = "sync-ssl")]
 use stream::sync::NetworkStream;
@@ -109,6 +132,13 @@ pub struct ClientBuilder<'u> {
 	headers: Headers,
 	version_set: bool,
 	key_set: bool,
+	max_message_size: Option<usize>,
+	verify_protocol: bool,
+	connect_timeout: Option<Duration>,
+	bind_address: Option<SocketAddr>,
+	#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
+	accept_invalid_certs: bool,
+	read_buffer_capacity: Option<usize>,
 }
 
 impl<'u> ClientBuilder<'u> {
@@ -155,12 +185,21 @@ impl<'u> ClientBuilder<'u> {
 			version: HttpVersion::Http11,
 			version_set: false,
 			key_set: false,
+			max_message_size: None,
+			verify_protocol: true,
+			connect_timeout: None,
+			bind_address: None,
+			#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
+			accept_invalid_certs: false,
+			read_buffer_capacity: None,
 			headers: Headers::new(),
 		}
 	}
 
 	/// Adds a user-defined protocol to the handshake, the server will be
 	/// given a list of these protocols and will send back the ones it accepts.
+	/// Protocols are offered in the order they are added, so the preferred
+	/// ones should be added first.
 	///
 	/// ```rust
 	/// # use websocket::ClientBuilder;
@@ -215,11 +254,24 @@ impl<'u> ClientBuilder<'u> {
 		self
 	}
 
+	/// Makes the handshake fail if the server accepts a protocol that was
+	/// not offered with `add_protocol` or `add_protocols`, as RFC6455 requires.
+	///
+	/// The handshake then fails with a `ProtocolError`. The server is still
+	/// allowed to accept none of the offered protocols, check
+	/// `Client::accepted_protocol` if one is required.
+	/// This is enabled by default, disabling it accepts any protocol.
+	pub fn verify_protocol(mut self, verify: bool) -> Self {
+		self.verify_protocol = verify;
+		self
+	}
+
 	/// Adds an extension to the connection.
 	/// Unlike protocols, extensions can be below the application level
-	/// (like compression). Currently no extensions are supported
-	/// out-of-the-box but one can still use them by using their own
-	/// implementation. Support is coming soon though.
+	/// (like compression). The only extension supported out-of-the-box is
+	/// permessage-deflate (see the `deflate` module), which synchronous clients
+	/// enable when the server accepts it. Other extensions can still be used
+	/// by using their own implementation.
 	///
 	/// ```rust
 	/// # use websocket::ClientBuilder;
@@ -243,8 +295,8 @@ impl<'u> ClientBuilder<'u> {
 	}
 
 	/// Adds some extensions to the connection.
-	/// Currently no extensions are supported out-of-the-box but one can
-	/// still use them by using their own implementation. Support is coming soon though.
+	/// The only extension supported out-of-the-box is permessage-deflate, but
+	/// one can still use others by using their own implementation.
 	///
 	/// ```rust
 	/// # use websocket::ClientBuilder;
@@ -287,6 +339,12 @@ impl<'u> ClientBuilder<'u> {
 	/// Add a custom `Sec-WebSocket-Key` header.
 	/// Use this only if you know what you're doing, and this almost
 	/// never has to be used.
+	///
+	/// The key is sent base64 encoded, and the `Sec-WebSocket-Accept` of the
+	/// server is checked against it. RFC6455 requires a new random key for
+	/// every handshake, which the builder generates unless a key is set
+	/// here, so a fixed key is only meant to make handshakes reproducible
+	/// in tests.
 	pub fn key(mut self, key: [u8; 16]) -> Self {
 		self.headers.set(WebSocketKey(key));
 		self.key_set = true;
@@ -317,12 +375,97 @@ impl<'u> ClientBuilder<'u> {
 		self
 	}
 
+	/// Limit the size of the messages the client will receive, see
+	/// `Receiver::set_max_message_size` for how the limit is applied.
+	///
+	/// This only applies to synchronous clients.
+	pub fn max_message_size(mut self, max_message_size: usize) -> Self {
+		self.max_message_size = Some(max_message_size);
+		self
+	}
+
+	/// Remove the limit on the size of received messages.
+	pub fn clear_max_message_size(mut self) -> Self {
+		self.max_message_size = None;
+		self
+	}
+
+	/// Sets the capacity in bytes of the buffer the client reads the stream
+	/// through, instead of the default of 4KiB. A larger buffer needs fewer
+	/// reads from the stream for large messages.
+	///
+	/// This only applies to synchronous clients.
+	pub fn read_buffer_capacity(mut self, capacity: usize) -> Self {
+		self.read_buffer_capacity = Some(capacity);
+		self
+	}
+
+	/// Bound the time `connect`, `connect_insecure` and `connect_secure` take,
+	/// from the first TCP connection attempt to the end of the WebSocket
+	/// handshake, including the TLS handshake. All the addresses of the host
+	/// are tried in turn within that time.
+	///
+	/// When the time runs out the connection is shut down and an
+	/// `WebSocketError::IoError` with a kind of `TimedOut` is returned.
+	/// Resolving the host name is not interrupted, but the time it takes is
+	/// taken into account. This only applies to synchronous clients.
+	pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+		self.connect_timeout = Some(timeout);
+		self
+	}
+
+	/// Bind the TCP connections of `connect`, `connect_insecure` and
+	/// `connect_secure` to the given local address before connecting, for
+	/// example to pick the interface used on a host with several of them.
+	///
+	/// Every address of the host is tried from that same local address, so
+	/// the addresses of the other IP version fail to connect. A port of 0
+	/// lets the system choose the port. This only applies to synchronous
+	/// clients.
+	pub fn bind_address(mut self, local: SocketAddr) -> Self {
+		self.bind_address = Some(local);
+		self
+	}
+
+	/// Makes secure connections accept any certificate the server presents,
+	/// for any host name, when given `true`.
+	///
+	/// **This is dangerous and must never be used in production**, anyone
+	/// able to intercept the connection can then read and change all the
+	/// messages. It is only meant for testing against local servers with a
+	/// self-signed certificate. It only applies to the connector made by the
+	/// builder, not to one passed to `connect`, `connect_secure` or their
+	/// async versions, and has no effect on insecure connections.
+	#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
+	pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+		self.accept_invalid_certs = accept;
+		self
+	}
+
 	/// Sets the Origin header of the handshake.
 	/// Normally in browsers this is used to protect against
 	/// unauthorized cross-origin use of a WebSocket server, but it is rarely
 	/// send by non-browser clients. Still, it can be useful.
-	pub fn origin(mut self, origin: String) -> Self {
-		self.headers.set(Origin(origin));
+	///
+	/// The origin must be serialized like browsers do it, either as
+	/// `scheme://host` with an optional non-default port and nothing else, or
+	/// as `null`, otherwise connecting fails with a `RequestError`.
+	/// Setting the origin again replaces the previous one.
+	///
+	/// ```rust
+	/// # use websocket::ClientBuilder;
+	/// use websocket::header::Origin;
+	///
+	/// let builder = ClientBuilder::new("wss://chat.example.com").unwrap()
+	///     .origin("https://example.com");
+	///
+	/// assert_eq!(builder.get_header::<Origin>().unwrap().0, "https://example.com");
+	/// ```
+	pub fn origin<O>(mut self, origin: O) -> Self
+	where
+		O: Into<String>,
+	{
+		self.headers.set(Origin(origin.into()));
 		self
 	}
 
@@ -352,6 +495,43 @@ impl<'u> ClientBuilder<'u> {
 		self
 	}
 
+	/// Adds a header to the handshake by name, for headers like
+	/// `Authorization`, cookies or tracing IDs that have no builder method.
+	///
+	/// Adding a header that is already set adds another value to it, so
+	/// multi-valued headers are sent once per value. The headers the
+	/// handshake itself relies on, `Upgrade`, `Connection`,
+	/// `Sec-WebSocket-Key` and `Sec-WebSocket-Version`, are ignored, use
+	/// `key` and `version` to change the last two.
+	///
+	/// ```rust
+	/// # use websocket::ClientBuilder;
+	/// let request = ClientBuilder::new("ws://moz.illest").unwrap()
+	///     .add_header("Authorization", "Bearer let-me-in")
+	///     .add_header("Cookie", "flavor=chocolate")
+	///     .build_request();
+	///
+	/// assert!(request.contains("Authorization: Bearer let-me-in\r\n"));
+	/// assert!(request.contains("Cookie: flavor=chocolate\r\n"));
+	/// ```
+	pub fn add_header<K, V>(mut self, name: K, value: V) -> Self
+	where
+		K: Into<Cow<'static, str>>,
+		V: Into<Vec<u8>>,
+	{
+		let name = name.into();
+		let reserved = [
+			"Upgrade",
+			"Connection",
+			"Sec-WebSocket-Key",
+			"Sec-WebSocket-Version",
+		];
+		if !reserved.iter().any(|r| r.eq_ignore_ascii_case(&name)) {
+			self.headers.append_raw(name, value.into());
+		}
+		self
+	}
+
 	/// Remove a type of header from the handshake, this is to be used
 	/// with the catch all `custom_headers`.
 	pub fn clear_header<H>(mut self) -> Self
@@ -394,15 +574,15 @@ impl<'u> ClientBuilder<'u> {
 		&mut self,
 		ssl_config: Option<TlsConnector>,
 	) -> WebSocketResult<Client<Box<NetworkStream + Send>>> {
-		let tcp_stream = self.establish_tcp(None)?;
-
-		let boxed_stream: Box<NetworkStream + Send> = if self.url.scheme() == "wss" {
-			Box::new(self.wrap_ssl(tcp_stream, ssl_config)?)
-		} else {
-			Box::new(tcp_stream)
-		};
+		self.connect_tcp(None, |builder, tcp_stream| {
+			let boxed_stream: Box<NetworkStream + Send> = if builder.url.scheme() == "wss" {
+				Box::new(builder.wrap_ssl(tcp_stream, ssl_config)?)
+			} else {
+				Box::new(tcp_stream)
+			};
 
-		self.connect_on(boxed_stream)
+			builder.connect_on(boxed_stream)
+		})
 	}
 
 	/// Create an insecure (plain TCP) connection to the client.
@@ -421,9 +601,41 @@ impl<'u> ClientBuilder<'u> {
 	/// ```
 	#[cfg(feature = "sync")]
 	pub fn connect_insecure(&mut self) -> WebSocketResult<Client<TcpStream>> {
-		let tcp_stream = self.establish_tcp(Some(false))?;
+		self.connect_tcp(Some(false), |builder, tcp_stream| {
+			builder.connect_on(tcp_stream)
+		})
+	}
 
-		self.connect_on(tcp_stream)
+	/// Create an insecure (plain TCP) connection like `connect_insecure`, but
+	/// only accept it if `accept` returns true for the headers of the
+	/// server's `101 Switching Protocols` response.
+	///
+	/// This lets the connection be refused because of headers like session
+	/// ids or feature flags before any message is exchanged. If `accept`
+	/// returns false the TCP connection is shut down and a
+	/// `WebSocketError::ResponseError` is returned.
+	///
+	/// ```rust,no_run
+	/// # use websocket::ClientBuilder;
+	/// let mut client = ClientBuilder::new("ws://127.0.0.1:1234").unwrap()
+	///     .connect_with(|headers| headers.get_raw("X-Session-Id").is_some())
+	///     .unwrap();
+	/// ```
+	#[cfg(feature = "sync")]
+	pub fn connect_with<F>(&mut self, accept: F) -> WebSocketResult<Client<TcpStream>>
+	where
+		F: FnOnce(&Headers) -> bool,
+	{
+		self.connect_tcp(Some(false), |builder, tcp_stream| {
+			let client = builder.connect_on(tcp_stream)?;
+			if !accept(client.headers()) {
+				let _ = client.shutdown();
+				return Err(WebSocketError::ResponseError(
+					"Response headers were rejected",
+				));
+			}
+			Ok(client)
+		})
 	}
 
 	/// Create an SSL connection to the sever.
@@ -435,14 +647,17 @@ impl<'u> ClientBuilder<'u> {
 		&mut self,
 		ssl_config: Option<TlsConnector>,
 	) -> WebSocketResult<Client<TlsStream<TcpStream>>> {
-		let tcp_stream = self.establish_tcp(Some(true))?;
-
-		let ssl_stream = self.wrap_ssl(tcp_stream, ssl_config)?;
+		self.connect_tcp(Some(true), |builder, tcp_stream| {
+			let ssl_stream = builder.wrap_ssl(tcp_stream, ssl_config)?;
 
-		self.connect_on(ssl_stream)
+			builder.connect_on(ssl_stream)
+		})
 	}
 
 	/// Connects to a websocket server on any stream you would like.
+	/// No connection is opened, the handshake is sent over the given stream with
+	/// the headers, protocols and extensions of this builder and the response is
+	/// validated just like with the other `connect` methods.
 	/// Possible streams:
 	///  - Unix Sockets
 	///  - Logging Middle-ware
@@ -476,19 +691,70 @@ impl<'u> ClientBuilder<'u> {
 	where
 		S: Stream,
 	{
+		self.validate_origin()?;
+
 		// send request
-		let resource = self.build_request();
-		let data = format!("GET {} {}\r\n{}\r\n", resource, self.version, self.headers);
+		let data = self.request_text();
 		stream.write_all(data.as_bytes())?;
 
 		// wait for a response
-		let mut reader = BufReader::new(stream);
+		let mut reader = match self.read_buffer_capacity {
+			Some(capacity) => BufReader::with_capacity(stream, capacity),
+			None => BufReader::new(stream),
+		};
 		let response = parse_response(&mut reader)?;
 
-		// validate
-		self.validate(&response)?;
+		// validate, keeping the start of the body when the server did not upgrade
+		match self.validate(&response) {
+			Err(WebSocketError::HandshakeFailed { status, reason, .. }) if status != 101 => {
+				return Err(WebSocketError::HandshakeFailed {
+					status,
+					reason,
+					body_snippet: body_snippet(&mut reader, &response.headers),
+				});
+			}
+			result => result?,
+		}
+
+		#[cfg(feature = "deflate")]
+		let deflate = self.negotiated_deflate(&response.headers)?;
+
+		let mut client = Client::unchecked(reader, response.headers, true, false);
+		client.set_max_message_size(self.max_message_size);
+		#[cfg(feature = "deflate")]
+		client.set_deflate(deflate)?;
+		Ok(client)
+	}
+
+	/// Reads the permessage-deflate parameters accepted by the server, making
+	/// sure that the extension was offered in the first place.
+	#[cfg(all(feature = "sync", feature = "deflate"))]
+	fn negotiated_deflate(&self, headers: &Headers) -> WebSocketResult<Option<DeflateConfig>> {
+		let accepted = match headers.get::<WebSocketExtensions>() {
+			Some(extensions) => extensions
+				.iter()
+				.find(|e| e.name == deflate::EXTENSION_NAME),
+			None => None,
+		};
+		let accepted = match accepted {
+			Some(extension) => extension,
+			None => return Ok(None),
+		};
+
+		let offered = self
+			.headers
+			.get::<WebSocketExtensions>()
+			.map(|e| e.iter().any(|e| e.name == deflate::EXTENSION_NAME))
+			.unwrap_or(false);
+		if !offered {
+			return Err(WebSocketError::ResponseError(
+				"Server accepted an extension that was not offered",
+			));
+		}
 
-		Ok(Client::unchecked(reader, response.headers, true, false))
+		DeflateConfig::from_extension(accepted)
+			.map(Some)
+			.map_err(|_| WebSocketError::ResponseError("Invalid permessage-deflate response"))
 	}
 
 	/// Connect to a websocket server asynchronously.
@@ -549,6 +815,12 @@ impl<'u> ClientBuilder<'u> {
 			headers: self.headers,
 			version_set: self.version_set,
 			key_set: self.key_set,
+			max_message_size: self.max_message_size,
+			verify_protocol: self.verify_protocol,
+			connect_timeout: self.connect_timeout,
+			bind_address: self.bind_address,
+			accept_invalid_certs: self.accept_invalid_certs,
+			read_buffer_capacity: self.read_buffer_capacity,
 		};
 
 		// check if we should connect over ssl or not
@@ -634,6 +906,12 @@ impl<'u> ClientBuilder<'u> {
 			headers: self.headers,
 			version_set: self.version_set,
 			key_set: self.key_set,
+			max_message_size: self.max_message_size,
+			verify_protocol: self.verify_protocol,
+			connect_timeout: self.connect_timeout,
+			bind_address: self.bind_address,
+			accept_invalid_certs: self.accept_invalid_certs,
+			read_buffer_capacity: self.read_buffer_capacity,
 		};
 
 		// put it all together
@@ -685,6 +963,13 @@ impl<'u> ClientBuilder<'u> {
 			headers: self.headers,
 			version_set: self.version_set,
 			key_set: self.key_set,
+			max_message_size: self.max_message_size,
+			verify_protocol: self.verify_protocol,
+			connect_timeout: self.connect_timeout,
+			bind_address: self.bind_address,
+			#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
+			accept_invalid_certs: self.accept_invalid_certs,
+			read_buffer_capacity: self.read_buffer_capacity,
 		};
 
 		let future = tcp_stream.and_then(move |stream| builder.async_connect_on(stream));
@@ -726,6 +1011,7 @@ impl<'u> ClientBuilder<'u> {
 	///
 	/// let client = ClientBuilder::new("wss://test.ws").unwrap()
 	///     .key(b"the sample nonce".clone())
+	///     .add_protocol("proto-metheus")
 	///     .async_connect_on(ReadWritePair(input, output))
 	///     .map(|(_, headers)| {
 	///         let proto: &WebSocketProtocol = headers.get().unwrap();
@@ -745,8 +1031,18 @@ impl<'u> ClientBuilder<'u> {
 			headers: self.headers,
 			version_set: self.version_set,
 			key_set: self.key_set,
+			max_message_size: self.max_message_size,
+			verify_protocol: self.verify_protocol,
+			connect_timeout: self.connect_timeout,
+			bind_address: self.bind_address,
+			#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
+			accept_invalid_certs: self.accept_invalid_certs,
+			read_buffer_capacity: self.read_buffer_capacity,
 		};
-		let resource = builder.build_request();
+		if let Err(e) = builder.validate_origin() {
+			return Box::new(future::err(e));
+		}
+		let resource = builder.prepare_request();
 		let framed = ::codec::http::HttpClientCodec.framed(stream);
 		let request = Incoming {
 			version: builder.version,
@@ -806,8 +1102,38 @@ impl<'u> ClientBuilder<'u> {
 		Box::new(TcpStreamNew::connect(&address).map_err(|e| e.into()))
 	}
 
+	/// Builds the handshake request that `connect` and friends would send,
+	/// without connecting, so that it can be logged or inspected.
+	///
+	/// The request has all the configured headers, protocols, extensions and
+	/// origin. Unless a key was set with `key`, every handshake uses a new
+	/// random `Sec-WebSocket-Key`, so the key in the returned request is not
+	/// the one a later `connect` will send.
+	///
+	/// ```rust
+	/// # use websocket::ClientBuilder;
+	/// let request = ClientBuilder::new("ws://test.ws/chat").unwrap()
+	///     .key(b"the sample nonce".clone())
+	///     .add_protocol("chat")
+	///     .build_request();
+	///
+	/// assert!(request.starts_with("GET /chat HTTP/1.1\r\n"));
+	/// assert!(request.contains("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n"));
+	/// assert!(request.contains("Sec-WebSocket-Protocol: chat\r\n"));
+	/// ```
+	#[cfg(any(feature = "sync", feature = "async"))]
+	pub fn build_request(&self) -> String {
+		self.clone().request_text()
+	}
+
+	#[cfg(any(feature = "sync", feature = "async"))]
+	fn request_text(&mut self) -> String {
+		let resource = self.prepare_request();
+		format!("GET {} {}\r\n{}\r\n", resource, self.version, self.headers)
+	}
+
 	#[cfg(any(feature = "sync", feature = "async"))]
-	fn build_request(&mut self) -> String {
+	fn prepare_request(&mut self) -> String {
 		// enter host if available (unix sockets don't have hosts)
 		if let Some(host) = self.url.host_str() {
 			self.headers.set(Host {
@@ -838,14 +1164,37 @@ impl<'u> ClientBuilder<'u> {
 		self.url[Position::BeforePath..Position::AfterQuery].to_owned()
 	}
 
+	#[cfg(any(feature = "sync", feature = "async"))]
+	fn validate_origin(&self) -> WebSocketResult<()> {
+		let origin = match self.headers.get::<Origin>() {
+			Some(origin) => origin.0.to_lowercase(),
+			None => return Ok(()),
+		};
+		if origin == "null" {
+			return Ok(());
+		}
+
+		match Url::parse(&origin) {
+			Ok(ref url) if url.origin().ascii_serialization() == origin => Ok(()),
+			_ => Err(WebSocketError::RequestError(
+				"Origin header is not a valid origin",
+			)),
+		}
+	}
+
 	#[cfg(any(feature = "sync", feature = "async"))]
 	fn validate(&self, response: &Incoming<RawStatus>) -> WebSocketResult<()> {
 		let status = StatusCode::from_u16(response.subject.0);
 
+		if let Some(offered) = offered_versions(response) {
+			return Err(WebSocketError::UnsupportedVersion { offered });
+		}
 		if status != StatusCode::SwitchingProtocols {
-			return Err(WebSocketError::ResponseError(
-				"Status code must be Switching Protocols",
-			));
+			return Err(WebSocketError::HandshakeFailed {
+				status: response.subject.0,
+				reason: "unexpected status",
+				body_snippet: String::new(),
+			});
 		}
 
 		let key = self
@@ -856,9 +1205,11 @@ impl<'u> ClientBuilder<'u> {
 			))?;
 
 		if response.headers.get() != Some(&(WebSocketAccept::new(key))) {
-			return Err(WebSocketError::ResponseError(
-				"Sec-WebSocket-Accept is invalid",
-			));
+			return Err(WebSocketError::HandshakeFailed {
+				status: response.subject.0,
+				reason: "accept mismatch",
+				body_snippet: String::new(),
+			});
 		}
 
 		if response.headers.get()
@@ -884,6 +1235,25 @@ impl<'u> ClientBuilder<'u> {
 			));
 		}
 
+		if self.verify_protocol {
+			let offered = self
+				.headers
+				.get::<WebSocketProtocol>()
+				.map(|p| p.0.as_slice())
+				.unwrap_or(&[]);
+			let accepted = response
+				.headers
+				.get::<WebSocketProtocol>()
+				.and_then(|p| p.0.iter().find(|p| !p.is_empty()));
+			if let Some(accepted) = accepted {
+				if !offered.contains(accepted) {
+					return Err(WebSocketError::ProtocolError(
+						"Server accepted a protocol that was not offered",
+					));
+				}
+			}
+		}
+
 		Ok(())
 	}
 
@@ -909,9 +1279,71 @@ impl<'u> ClientBuilder<'u> {
 		})?)
 	}
 
+	/// Opens a TCP connection and finishes the connection with `connect`,
+	/// within the `connect_timeout` if there is one.
 	#[cfg(feature = "sync")]
-	fn establish_tcp(&mut self, secure: Option<bool>) -> WebSocketResult<TcpStream> {
-		Ok(TcpStream::connect(self.extract_host_port(secure)?)?)
+	fn connect_tcp<S, F>(&mut self, secure: Option<bool>, connect: F) -> WebSocketResult<S>
+	where
+		F: FnOnce(&mut Self, TcpStream) -> WebSocketResult<S>,
+	{
+		let deadline = match self.connect_timeout {
+			Some(timeout) => Instant::now() + timeout,
+			None => {
+				let tcp_stream = self.establish_tcp(secure, None)?;
+				return connect(self, tcp_stream);
+			}
+		};
+
+		let tcp_stream = self.establish_tcp(secure, Some(deadline))?;
+		let watchdog = Watchdog::start(&tcp_stream, deadline)?;
+		let result = connect(self, tcp_stream);
+		if watchdog.stop() {
+			return Err(timed_out());
+		}
+		result
+	}
+
+	#[cfg(feature = "sync")]
+	fn establish_tcp(
+		&mut self,
+		secure: Option<bool>,
+		deadline: Option<Instant>,
+	) -> WebSocketResult<TcpStream> {
+		let address = self.extract_host_port(secure)?;
+		if deadline.is_none() && self.bind_address.is_none() {
+			return Ok(TcpStream::connect(address)?);
+		}
+
+		let mut last_error = None;
+		for address in address.to_socket_addrs()? {
+			let timeout = match deadline {
+				Some(deadline) => {
+					let now = Instant::now();
+					if now >= deadline {
+						return Err(timed_out());
+					}
+					Some(deadline - now)
+				}
+				None => None,
+			};
+			let stream = match (self.bind_address, timeout) {
+				(Some(local), timeout) => connect_from(local, &address, timeout),
+				(None, Some(timeout)) => TcpStream::connect_timeout(&address, timeout),
+				(None, None) => TcpStream::connect(address),
+			};
+			match stream {
+				Ok(stream) => return Ok(stream),
+				Err(e) => last_error = Some(e),
+			}
+		}
+		Err(last_error
+			.unwrap_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidInput,
+					"could not resolve to any addresses",
+				)
+			})
+			.into())
 	}
 
 	#[cfg(any(feature = "sync-ssl", feature = "async-ssl"))]
@@ -929,7 +1361,10 @@ impl<'u> ClientBuilder<'u> {
 		};
 		let connector = match connector {
 			Some(c) => c,
-			None => TlsConnector::builder().build()?,
+			None => TlsConnector::builder()
+				.danger_accept_invalid_certs(self.accept_invalid_certs)
+				.danger_accept_invalid_hostnames(self.accept_invalid_certs)
+				.build()?,
 		};
 		Ok((host, connector))
 	}
@@ -946,6 +1381,115 @@ impl<'u> ClientBuilder<'u> {
 	}
 }
 
+/// Connects to `remote` from the local address `local`, giving up after
+/// `timeout` if there is one.
+#[cfg(feature = "sync")]
+fn connect_from(
+	local: SocketAddr,
+	remote: &SocketAddr,
+	timeout: Option<Duration>,
+) -> io::Result<TcpStream> {
+	let socket = Socket::new(Domain::for_address(*remote), Type::STREAM, None)?;
+	socket.bind(&local.into())?;
+	match timeout {
+		Some(timeout) => socket.connect_timeout(&(*remote).into(), timeout)?,
+		None => socket.connect(&(*remote).into())?,
+	}
+	Ok(socket.into())
+}
+
+/// Shuts a connection down once its deadline passes, so that a blocked TLS
+/// or WebSocket handshake returns.
+#[cfg(feature = "sync")]
+struct Watchdog {
+	// set by whichever of the watchdog or the connection finishes first
+	settled: Arc<Mutex<bool>>,
+	stop: mpsc::Sender<()>,
+}
+
+#[cfg(feature = "sync")]
+impl Watchdog {
+	fn start(stream: &TcpStream, deadline: Instant) -> io::Result<Watchdog> {
+		let stream = stream.try_clone()?;
+		let settled = Arc::new(Mutex::new(false));
+		let (stop, stopped) = mpsc::channel();
+
+		let watched = settled.clone();
+		thread::spawn(move || {
+			let now = Instant::now();
+			if now < deadline && stopped.recv_timeout(deadline - now).is_ok() {
+				return;
+			}
+			let mut settled = watched.lock().unwrap_or_else(|e| e.into_inner());
+			if !*settled {
+				*settled = true;
+				let _ = stream.shutdown(Shutdown::Both);
+			}
+		});
+
+		Ok(Watchdog { settled, stop })
+	}
+
+	/// Stops the watchdog, returning whether the deadline had already passed.
+	fn stop(self) -> bool {
+		let mut settled = self.settled.lock().unwrap_or_else(|e| e.into_inner());
+		if *settled {
+			return true;
+		}
+		*settled = true;
+		let _ = self.stop.send(());
+		false
+	}
+}
+
+#[cfg(feature = "sync")]
+fn timed_out() -> WebSocketError {
+	WebSocketError::IoError(io::Error::new(
+		io::ErrorKind::TimedOut,
+		"connection timed out",
+	))
+}
+
+/// The versions listed by a `426 Upgrade Required` response, if it is one.
+#[cfg(any(feature = "sync", feature = "async"))]
+fn offered_versions(response: &Incoming<RawStatus>) -> Option<Vec<u8>> {
+	if response.subject.0 != 426 {
+		return None;
+	}
+	let raw = response.headers.get_raw("Sec-WebSocket-Version")?;
+	Some(
+		raw.iter()
+			.flat_map(|value| value.split(|&b| b == b','))
+			.filter_map(|version| String::from_utf8_lossy(version).trim().parse().ok())
+			.collect(),
+	)
+}
+
+/// The most bytes of a failed handshake response body kept in the error.
+#[cfg(feature = "sync")]
+const BODY_SNIPPET_LEN: u64 = 512;
+
+/// Reads the start of the body of a response that is not an upgrade, as
+/// much of it as the `Content-Length` announces or else what is already
+/// buffered, so that this never waits for a body that isn't coming.
+#[cfg(feature = "sync")]
+fn body_snippet<S: Read>(reader: &mut BufReader<S>, headers: &Headers) -> String {
+	let mut body = Vec::new();
+	match headers.get::<ContentLength>() {
+		Some(&ContentLength(len)) => {
+			let _ = reader
+				.take(cmp::min(len, BODY_SNIPPET_LEN))
+				.read_to_end(&mut body);
+		}
+		None => {
+			let buffered = reader.get_buf();
+			let len = cmp::min(buffered.len(), BODY_SNIPPET_LEN as usize);
+			body.extend_from_slice(&buffered[..len]);
+		}
+	}
+	String::from_utf8_lossy(&body).into_owned()
+}
+
 mod tests {
 	#[test]
 	fn build_client_with_protocols() {
@@ -970,4 +1514,573 @@ mod tests {
 		assert!(protos.contains(&"electric".to_string()));
 		assert!(!protos.contains(&"rust-websocket".to_string()));
 	}
+
+	#[test]
+	#[cfg(all(feature = "sync", feature = "deflate"))]
+	fn connect_with_permessage_deflate() {
+		use super::*;
+		use message::OwnedMessage;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let mut input = b"HTTP/1.1 101 Switching Protocols\r\n\
+			Upgrade: websocket\r\n\
+			Connection: Upgrade\r\n\
+			Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+			Sec-WebSocket-Extensions: permessage-deflate\r\n\
+			\r\n"
+			.to_vec();
+		// a compressed, unmasked "Hello" text message (RFC7692 section 7.2.3.1)
+		input.extend_from_slice(&[0xc1, 0x07, 0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00]);
+
+		let mut client = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.add_extension(Extension::new("permessage-deflate".to_string()))
+			.connect_on(ReadWritePair(
+				Cursor::new(input.clone()),
+				Cursor::new(Vec::new()),
+			))
+			.unwrap();
+		assert!(client.deflate().is_some());
+		assert_eq!(
+			client.recv_message().unwrap(),
+			OwnedMessage::Text("Hello".to_string())
+		);
+
+		// the extension must have been offered to be accepted
+		let result = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.connect_on(ReadWritePair(Cursor::new(input), Cursor::new(Vec::new())));
+		assert!(result.is_err());
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn verify_accepted_protocol() {
+		use super::*;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let response = |protocol: &str| {
+			let response = format!(
+				"HTTP/1.1 101 Switching Protocols\r\n\
+				 Upgrade: websocket\r\n\
+				 Connection: Upgrade\r\n\
+				 Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+				 Sec-WebSocket-Protocol: {}\r\n\
+				 \r\n",
+				protocol
+			);
+			ReadWritePair(Cursor::new(response.into_bytes()), Cursor::new(Vec::new()))
+		};
+		let builder = || {
+			ClientBuilder::new("ws://test.ws")
+				.unwrap()
+				.key(*b"the sample nonce")
+				.add_protocols(vec!["chat", "superchat"])
+				.verify_protocol(true)
+		};
+
+		let client = builder().connect_on(response("superchat")).unwrap();
+		assert_eq!(client.accepted_protocol(), Some("superchat".to_string()));
+		// the protocols are offered in the order they were added
+		let request = String::from_utf8((client.into_stream().0).1.into_inner()).unwrap();
+		assert!(request.contains("Sec-WebSocket-Protocol: chat, superchat\r\n"));
+
+		assert!(builder().connect_on(response("otherchat")).is_err());
+		// the accepted protocol is verified by default
+		let result = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.add_protocol("chat")
+			.connect_on(response("otherchat"));
+		match result {
+			Err(WebSocketError::ProtocolError(_)) => {}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("unoffered protocol was accepted"),
+		}
+		// without verification the handshake still succeeds
+		let client = builder()
+			.verify_protocol(false)
+			.connect_on(response("otherchat"))
+			.unwrap();
+		assert_eq!(client.accepted_protocol(), Some("otherchat".to_string()));
+		assert!(!client.accepted_protocol_offered(&["chat", "superchat"]));
+		assert!(client.accepted_protocol_offered(&["otherchat"]));
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn handshake_with_origin() {
+		use super::*;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let response = b"HTTP/1.1 101 Switching Protocols\r\n\
+			Upgrade: websocket\r\n\
+			Connection: Upgrade\r\n\
+			Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+			\r\n";
+
+		let client = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.origin("http://first.example")
+			.origin("https://example.com:8443")
+			.connect_on(ReadWritePair(
+				Cursor::new(response.to_vec()),
+				Cursor::new(Vec::new()),
+			))
+			.unwrap();
+		let request = String::from_utf8((client.into_stream().0).1.into_inner()).unwrap();
+		assert!(
+			request.contains("Origin: https://example.com:8443\r\n"),
+			"{}",
+			request
+		);
+		assert_eq!(request.matches("Origin:").count(), 1);
+
+		for origin in &["https://example.com/path", "example.com", ""] {
+			let result = ClientBuilder::new("ws://test.ws")
+				.unwrap()
+				.origin(*origin)
+				.connect_on(ReadWritePair(
+					Cursor::new(response.to_vec()),
+					Cursor::new(Vec::new()),
+				));
+			match result {
+				Err(WebSocketError::RequestError(_)) => {}
+				_ => panic!("origin {:?} was accepted", origin),
+			}
+		}
+	}
+
+	#[test]
+	#[cfg(any(feature = "sync", feature = "async"))]
+	fn build_request_without_connecting() {
+		use super::*;
+		use base64;
+
+		let builder = ClientBuilder::new("ws://test.ws:8080/chat?room=1")
+			.unwrap()
+			.origin("http://test.ws".to_string())
+			.add_protocols(vec!["chat", "superchat"]);
+		let request = builder.build_request();
+
+		assert!(request.starts_with("GET /chat?room=1 HTTP/1.1\r\n"));
+		assert!(request.ends_with("\r\n\r\n"));
+		assert!(request.contains("Host: test.ws:8080\r\n"));
+		assert!(request.contains("Upgrade: websocket\r\n"));
+		assert!(request.contains("Connection: Upgrade\r\n"));
+		assert!(request.contains("Origin: http://test.ws\r\n"));
+		assert!(request.contains("Sec-WebSocket-Protocol: chat, superchat\r\n"));
+		let key = request
+			.lines()
+			.find(|line| line.starts_with("Sec-WebSocket-Key: "))
+			.map(|line| &line["Sec-WebSocket-Key: ".len()..])
+			.unwrap();
+		assert_eq!(base64::decode(key).unwrap().len(), 16);
+	}
+
+	#[test]
+	#[cfg(any(feature = "sync", feature = "async"))]
+	fn add_header_by_name() {
+		use super::*;
+
+		let request = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.add_header("Authorization", "Bearer let-me-in")
+			.add_header("X-Trace", "one")
+			.add_header("x-trace", "two")
+			.add_header("connection", "close")
+			.add_header("Sec-WebSocket-Key", "bm90IHRoZSBrZXk=")
+			.build_request();
+
+		assert!(request.contains("Authorization: Bearer let-me-in\r\n"));
+		assert!(request.contains("X-Trace: one\r\n"));
+		assert!(request.contains("X-Trace: two\r\n"));
+		assert!(request.contains("Connection: Upgrade\r\n"));
+		assert!(!request.contains("close"));
+		assert!(request.contains("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n"));
+		assert!(!request.contains("bm90IHRoZSBrZXk="));
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn fixed_key_is_sent_and_validated() {
+		use super::*;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let response = || {
+			let response = b"HTTP/1.1 101 Switching Protocols\r\n\
+				Upgrade: websocket\r\n\
+				Connection: Upgrade\r\n\
+				Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+				\r\n";
+			ReadWritePair(Cursor::new(&response[..]), Cursor::new(Vec::new()))
+		};
+
+		let builder = ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce");
+		assert!(builder
+			.build_request()
+			.contains("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n"));
+		let client = builder.clone().connect_on(response()).unwrap();
+		let request = String::from_utf8((client.into_stream().0).1.into_inner()).unwrap();
+		assert!(request.contains("Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n"));
+
+		// the accept is checked against the key that was set
+		match builder.key(*b"another nonce!!!").connect_on(response()) {
+			Err(WebSocketError::HandshakeFailed { reason, .. }) => {
+				assert_eq!(reason, "accept mismatch")
+			}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn read_buffer_capacity() {
+		use super::*;
+		use message::{Message, OwnedMessage};
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+		use ws::Message as MessageTrait;
+
+		struct CountingReader {
+			input: Cursor<Vec<u8>>,
+			reads: usize,
+		}
+
+		impl Read for CountingReader {
+			fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+				self.reads += 1;
+				self.input.read(buf)
+			}
+		}
+
+		let mut input = b"HTTP/1.1 101 Switching Protocols\r\n\
+			Upgrade: websocket\r\n\
+			Connection: Upgrade\r\n\
+			Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+			\r\n"
+			.to_vec();
+		Message::binary(vec![7; 256 * 1024])
+			.serialize(&mut input, false)
+			.unwrap();
+
+		let reads = |builder: ClientBuilder| {
+			let stream = ReadWritePair(
+				CountingReader {
+					input: Cursor::new(input.clone()),
+					reads: 0,
+				},
+				Cursor::new(Vec::new()),
+			);
+			let mut client = builder
+				.key(*b"the sample nonce")
+				.connect_on(stream)
+				.unwrap();
+			assert_eq!(
+				client.recv_message().unwrap(),
+				OwnedMessage::Binary(vec![7; 256 * 1024])
+			);
+			(client.read_buffer_capacity(), client.stream_ref().0.reads)
+		};
+
+		let builder = ClientBuilder::new("ws://test.ws").unwrap();
+		let (default_capacity, default_reads) = reads(builder.clone());
+		let (capacity, large_reads) = reads(builder.read_buffer_capacity(64 * 1024));
+		assert_eq!(default_capacity, 4 * 1024);
+		assert_eq!(capacity, 64 * 1024);
+		assert!(
+			large_reads < default_reads,
+			"{} reads with a 64KiB buffer, {} with the default",
+			large_reads,
+			default_reads
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn handshake_with_wrong_accept() {
+		use super::*;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let response = b"HTTP/1.1 101 Switching Protocols\r\n\
+			Upgrade: websocket\r\n\
+			Connection: Upgrade\r\n\
+			Sec-WebSocket-Accept: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+			\r\n";
+		let stream = ReadWritePair(Cursor::new(&response[..]), Cursor::new(Vec::new()));
+
+		match ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.key(*b"the sample nonce")
+			.connect_on(stream)
+		{
+			Err(WebSocketError::HandshakeFailed { status, reason, .. }) => {
+				assert_eq!(status, 101);
+				assert_eq!(reason, "accept mismatch");
+			}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn handshake_with_unsupported_version() {
+		use super::*;
+		use std::io::Cursor;
+		use stream::ReadWritePair;
+
+		let response = b"HTTP/1.1 426 Upgrade Required\r\n\
+			Sec-WebSocket-Version: 8, 13\r\n\
+			Content-Length: 0\r\n\
+			\r\n";
+		let stream = ReadWritePair(Cursor::new(&response[..]), Cursor::new(Vec::new()));
+
+		match ClientBuilder::new("ws://test.ws")
+			.unwrap()
+			.connect_on(stream)
+		{
+			Err(WebSocketError::UnsupportedVersion { offered }) => {
+				assert_eq!(offered, vec![8, 13]);
+			}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn handshake_answered_with_plain_http() {
+		use super::*;
+		use std::io::{Read, Write};
+		use std::net::TcpListener;
+		use std::thread;
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}/", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = [0; 1024];
+			let _ = stream.read(&mut request).unwrap();
+			stream
+				.write_all(
+					b"HTTP/1.1 200 OK\r\n\
+					Content-Type: text/html\r\n\
+					Content-Length: 29\r\n\
+					\r\n\
+					<html>Please log in.</html>\r\n",
+				)
+				.unwrap();
+		});
+
+		match ClientBuilder::new(&url).unwrap().connect_insecure() {
+			Err(WebSocketError::HandshakeFailed {
+				status,
+				reason,
+				body_snippet,
+			}) => {
+				assert_eq!(status, 200);
+				assert_eq!(reason, "unexpected status");
+				assert_eq!(body_snippet, "<html>Please log in.</html>\r\n");
+			}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+		server.join().unwrap();
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn connect_with_rejected_headers() {
+		use super::*;
+		use std::io::{Read, Write};
+		use std::net::TcpListener;
+		use std::thread;
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}/", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			for _ in 0..2 {
+				let (mut stream, _) = listener.accept().unwrap();
+				let mut request = [0; 1024];
+				let _ = stream.read(&mut request).unwrap();
+				stream
+					.write_all(
+						b"HTTP/1.1 101 Switching Protocols\r\n\
+						Upgrade: websocket\r\n\
+						Connection: Upgrade\r\n\
+						Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\
+						X-Feature: on\r\n\
+						\r\n",
+					)
+					.unwrap();
+				// the rejected connection is closed by the client
+				let mut rest = Vec::new();
+				stream.read_to_end(&mut rest).unwrap();
+				assert!(rest.is_empty());
+			}
+		});
+
+		let mut builder = ClientBuilder::new(&url).unwrap().key(*b"the sample nonce");
+		match builder.connect_with(|headers| headers.get_raw("X-Session-Id").is_some()) {
+			Err(WebSocketError::ResponseError(_)) => {}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+
+		let client = builder
+			.connect_with(|headers| headers.get_raw("X-Feature").is_some())
+			.unwrap();
+		drop(client);
+		server.join().unwrap();
+	}
+
+	#[test]
+	#[cfg(all(feature = "sync", unix))]
+	fn handshake_over_unix_socket() {
+		use super::*;
+		use message::OwnedMessage;
+		use server::upgrade::sync::IntoWs;
+		use std::os::unix::net::UnixStream;
+		use std::thread;
+
+		let (stream, server) = UnixStream::pair().unwrap();
+		let server = thread::spawn(move || {
+			let upgrade = server.into_ws().ok().unwrap();
+			assert_eq!(upgrade.protocols(), ["chat", "superchat"]);
+			assert_eq!(upgrade.extensions()[0].name, "x-test");
+			assert_eq!(
+				upgrade.request.headers.get_raw("X-Custom"),
+				Some(&[b"value".to_vec()][..])
+			);
+			let mut client = upgrade.use_protocol("chat").accept().ok().unwrap();
+			let message = client.recv_message().unwrap();
+			client.send_message(&message).unwrap();
+		});
+
+		let mut headers = Headers::new();
+		headers.set_raw("X-Custom", vec![b"value".to_vec()]);
+		let mut client = ClientBuilder::new("ws://localhost/socket")
+			.unwrap()
+			.add_protocols(vec!["chat", "superchat"])
+			.add_extension(Extension::new("x-test".to_string()))
+			.custom_headers(&headers)
+			.connect_on(stream)
+			.unwrap();
+		assert_eq!(client.protocols(), ["chat"]);
+
+		client
+			.send_message(&OwnedMessage::Text("echo".to_string()))
+			.unwrap();
+		assert_eq!(
+			client.recv_message().unwrap(),
+			OwnedMessage::Text("echo".to_string())
+		);
+		server.join().unwrap();
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn connect_from_bind_address() {
+		use super::*;
+		use server::upgrade::sync::IntoWs;
+		use std::io::ErrorKind;
+		use std::net::{IpAddr, TcpListener};
+		use std::thread;
+		use std::time::Instant;
+
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}/", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let (stream, peer) = listener.accept().unwrap();
+			let _client = stream.into_ws().ok().unwrap().accept().ok().unwrap();
+			peer
+		});
+
+		let client = ClientBuilder::new(&url)
+			.unwrap()
+			.bind_address("127.0.0.1:0".parse().unwrap())
+			.connect_timeout(Duration::from_secs(5))
+			.connect_insecure()
+			.unwrap();
+		let local = client.local_addr().unwrap();
+		assert_eq!(local.ip(), "127.0.0.1".parse::<IpAddr>().unwrap());
+		assert_eq!(server.join().unwrap(), local);
+
+		// a local address of the wrong IP version cannot connect
+		let result = ClientBuilder::new(&url)
+			.unwrap()
+			.bind_address("[::1]:0".parse().unwrap())
+			.connect_insecure();
+		assert!(result.is_err());
+
+		// the timeout bounds the connection attempt from a bound socket, here
+		// to a listener whose full backlog leaves the attempt unanswered
+		let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+		listener
+			.bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())
+			.unwrap();
+		listener.listen(0).unwrap();
+		let addr = listener.local_addr().unwrap().as_socket().unwrap();
+		let _queued = TcpStream::connect(addr).unwrap();
+		let start = Instant::now();
+		let result = ClientBuilder::new(&format!("ws://{}/", addr))
+			.unwrap()
+			.bind_address("127.0.0.1:0".parse().unwrap())
+			.connect_timeout(Duration::from_millis(200))
+			.connect_insecure();
+		match result {
+			Err(WebSocketError::IoError(ref e)) if e.kind() == ErrorKind::TimedOut => {}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("connected past a full backlog"),
+		}
+		assert!(start.elapsed() < Duration::from_secs(5));
+	}
+
+	#[test]
+	#[cfg(feature = "sync")]
+	fn connect_timeout_bounds_the_handshake() {
+		use super::*;
+		use std::io::{ErrorKind, Read};
+		use std::net::TcpListener;
+		use std::thread;
+		use std::time::Instant;
+
+		// accepts connections but never answers the handshake
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}/", listener.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let (mut stream, _) = listener.accept().unwrap();
+			let mut request = Vec::new();
+			let _ = stream.read_to_end(&mut request);
+		});
+
+		let start = Instant::now();
+		let result = ClientBuilder::new(&url)
+			.unwrap()
+			.connect_timeout(Duration::from_millis(200))
+			.connect_insecure();
+		let elapsed = start.elapsed();
+		match result {
+			Err(WebSocketError::IoError(ref e)) if e.kind() == ErrorKind::TimedOut => {}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(_) => panic!("handshake succeeded"),
+		}
+		assert!(elapsed >= Duration::from_millis(200), "{:?}", elapsed);
+		assert!(elapsed < Duration::from_secs(2), "{:?}", elapsed);
+		// the connection was shut down
+		server.join().unwrap();
+	}
 }
@@ -21,3 +21,9 @@ pub mod async;
 
 #[cfg(feature = "sync")]
 pub mod sync;
+
+#[cfg(feature = "sync")]
+pub mod reconnect;
+
+#[cfg(feature = "sync")]
+pub mod pool;
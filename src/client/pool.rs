@@ -0,0 +1,207 @@
+//! A pool of synchronous clients that reuses idle connections.
+use std::collections::HashMap;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+use client::builder::{ClientBuilder, Url};
+use client::sync::Client;
+use result::WebSocketResult;
+use stream::sync::{AsTcpStream, Stream};
+
+/// The way a `ClientPool` connects to a url it has no idle client for.
+type Connect<S> = Box<dyn FnMut(&Url) -> WebSocketResult<Client<S>> + Send>;
+
+struct Idle<S>
+where
+	S: Stream,
+{
+	client: Client<S>,
+	since: Instant,
+}
+
+/// A pool of clients, keeping the clients given back to it open so that the
+/// next client checked out for the same url does not need a new handshake.
+///
+/// Clients are kept by their parsed url, so urls differing only in the
+/// case of the host or in a default port share their clients. Before an
+/// idle client is handed out it is pinged, and it is dropped instead when
+/// the pong does not arrive within the ping timeout, in which case the next
+/// idle client or a new connection is tried. Any message the server sent
+/// while the client was idle is discarded by this ping. Clients idle for
+/// longer than the maximum idle time are dropped as well.
+///
+///```no_run
+///# extern crate websocket;
+///# fn main() {
+///use websocket::sync::client::ClientPool;
+///use websocket::Message;
+///
+///let mut pool = ClientPool::new();
+///let mut client = pool.checkout("ws://127.0.0.1:1234").unwrap();
+///client.send_message(&Message::text("Hello, World!")).unwrap();
+///pool.checkin("ws://127.0.0.1:1234", client).unwrap();
+///
+///// the same connection is used again
+///let client = pool.checkout("ws://127.0.0.1:1234").unwrap();
+///# }
+///```
+pub struct ClientPool<S>
+where
+	S: Stream,
+{
+	connect: Connect<S>,
+	idle: HashMap<Url, Vec<Idle<S>>>,
+	max_idle: Duration,
+	ping_timeout: Duration,
+}
+
+impl ClientPool<TcpStream> {
+	/// Creates a pool connecting with `ClientBuilder::connect_insecure`.
+	pub fn new() -> Self {
+		ClientPool::with_connect(|url| ClientBuilder::from_url(url).connect_insecure())
+	}
+}
+
+impl Default for ClientPool<TcpStream> {
+	fn default() -> Self {
+		ClientPool::new()
+	}
+}
+
+impl<S> ClientPool<S>
+where
+	S: AsTcpStream + Stream,
+{
+	/// Creates a pool connecting with the given function, for example
+	/// `|url| ClientBuilder::from_url(url).connect(None)` to also connect to
+	/// `wss://` urls.
+	pub fn with_connect<F>(connect: F) -> Self
+	where
+		F: FnMut(&Url) -> WebSocketResult<Client<S>> + Send + 'static,
+	{
+		ClientPool {
+			connect: Box::new(connect),
+			idle: HashMap::new(),
+			max_idle: Duration::from_secs(60),
+			ping_timeout: Duration::from_secs(1),
+		}
+	}
+
+	/// Sets how long a client is kept after it was given back,
+	/// 60 seconds by default.
+	pub fn max_idle(mut self, max_idle: Duration) -> Self {
+		self.max_idle = max_idle;
+		self
+	}
+
+	/// Sets how long to wait for the pong of an idle client before it is
+	/// handed out, 1 second by default.
+	pub fn ping_timeout(mut self, timeout: Duration) -> Self {
+		self.ping_timeout = timeout;
+		self
+	}
+
+	/// Hands out an idle client connected to `url` which still answers a
+	/// ping, or connects a new one if there is none.
+	pub fn checkout(&mut self, url: &str) -> WebSocketResult<Client<S>> {
+		let url = Url::parse(url)?;
+		self.evict_idle();
+		while let Some(mut idle) = self.idle.get_mut(&url).and_then(|idle| idle.pop()) {
+			if idle.client.ping_rtt(self.ping_timeout).is_ok() {
+				return Ok(idle.client);
+			}
+		}
+		(self.connect)(&url)
+	}
+
+	/// Gives a client connected to `url` back to the pool, to be handed out
+	/// again by `checkout`. Clients that are closed or closing are dropped.
+	pub fn checkin(&mut self, url: &str, client: Client<S>) -> WebSocketResult<()> {
+		let url = Url::parse(url)?;
+		if client.is_open() {
+			self.idle.entry(url).or_default().push(Idle {
+				client,
+				since: Instant::now(),
+			});
+		}
+		Ok(())
+	}
+
+	/// The number of idle clients kept for `url`.
+	pub fn idle_count(&self, url: &str) -> WebSocketResult<usize> {
+		let url = Url::parse(url)?;
+		Ok(self.idle.get(&url).map_or(0, |idle| idle.len()))
+	}
+
+	/// Drops the clients which have been idle for longer than the maximum
+	/// idle time. This also happens on every `checkout`.
+	pub fn evict_idle(&mut self) {
+		let max_idle = self.max_idle;
+		for idle in self.idle.values_mut() {
+			idle.retain(|idle| idle.since.elapsed() < max_idle);
+		}
+		self.idle.retain(|_, idle| !idle.is_empty());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use message::{Message, OwnedMessage};
+	use server::sync::Server;
+	use std::thread;
+
+	#[test]
+	fn reuses_a_live_client() {
+		let mut server = Server::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}", server.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			let mut client = server.accept().ok().unwrap().accept().ok().unwrap();
+			client.set_auto_pong(true);
+			let mut texts = Vec::new();
+			loop {
+				match client.recv_message().unwrap() {
+					OwnedMessage::Text(text) => texts.push(text),
+					OwnedMessage::Close(_) => break,
+					other => panic!("unexpected message {:?}", other),
+				}
+			}
+			client.send_message(&Message::close()).unwrap();
+			texts
+		});
+
+		let mut pool = ClientPool::new();
+		let mut client = pool.checkout(&url).unwrap();
+		let addr = client.local_addr().unwrap();
+		client.send_message(&Message::text("one")).unwrap();
+		pool.checkin(&url, client).unwrap();
+		assert_eq!(pool.idle_count(&url).unwrap(), 1);
+
+		let mut client = pool.checkout(&url).unwrap();
+		assert_eq!(client.local_addr().unwrap(), addr);
+		assert_eq!(pool.idle_count(&url).unwrap(), 0);
+		client.send_message(&Message::text("two")).unwrap();
+		client.close(None, None).unwrap();
+
+		// a closed client is not kept
+		pool.checkin(&url, client).unwrap();
+		assert_eq!(pool.idle_count(&url).unwrap(), 0);
+		assert_eq!(server.join().unwrap(), vec!["one", "two"]);
+	}
+
+	#[test]
+	fn evicts_clients_idle_for_too_long() {
+		let mut server = Server::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}", server.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			server.accept().ok().unwrap().accept().ok().unwrap();
+		});
+
+		let mut pool = ClientPool::new().max_idle(Duration::from_millis(0));
+		let client = pool.checkout(&url).unwrap();
+		pool.checkin(&url, client).unwrap();
+		pool.evict_idle();
+		assert_eq!(pool.idle_count(&url).unwrap(), 0);
+		server.join().unwrap();
+	}
+}
@@ -0,0 +1,306 @@
+//! A synchronous client that reconnects when its connection drops.
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+use client::builder::ClientBuilder;
+use client::sync::Client;
+use message::{CloseStatusCode, OwnedMessage};
+use result::{WebSocketError, WebSocketResult};
+use stream::sync::{AsTcpStream, Stream};
+use ws;
+
+/// The way a `ReconnectingClient` connects to the server.
+type Connect<'u, S> = Box<dyn FnMut(&mut ClientBuilder<'u>) -> WebSocketResult<Client<S>> + Send>;
+
+/// The function called with the new client after every reconnect.
+type OnReconnect<S> = Box<dyn FnMut(&mut Client<S>) + Send>;
+
+/// A client that transparently connects again, with the configuration of its
+/// `ClientBuilder`, when the connection drops while sending or receiving.
+///
+/// A dropped connection is one that ended without a close, or an IO error
+/// like a reset or a broken pipe. Timeouts and protocol errors are returned
+/// as they are, and once a close was sent or received the client never
+/// reconnects again. Reconnecting waits between the attempts, starting with
+/// the initial backoff and doubling it up to the maximum backoff, and gives
+/// up after `max_attempts` failed attempts, returning the last error.
+///
+/// The first connection is made by the first send or receive. Settings of
+/// the client like `set_auto_pong` are not carried over to a new connection,
+/// use `on_reconnect` to set them again, or to resubscribe to whatever the
+/// server should send.
+///
+///```no_run
+///# extern crate websocket;
+///# fn main() {
+///use std::time::Duration;
+///use websocket::ClientBuilder;
+///use websocket::sync::client::ReconnectingClient;
+///use websocket::Message;
+///
+///let builder = ClientBuilder::new("ws://127.0.0.1:1234").unwrap();
+///let mut client = ReconnectingClient::new(builder)
+///    .backoff(Duration::from_millis(100), Duration::from_secs(10))
+///    .on_reconnect(|client| client.set_auto_pong(true));
+///
+///client.send_message(&Message::text("Hello, World!")).unwrap();
+///let message = client.recv_message().unwrap();
+///# }
+///```
+pub struct ReconnectingClient<'u, S>
+where
+	S: Stream,
+{
+	builder: ClientBuilder<'u>,
+	connect: Connect<'u, S>,
+	client: Option<Client<S>>,
+	on_reconnect: Option<OnReconnect<S>>,
+	initial_backoff: Duration,
+	max_backoff: Duration,
+	max_attempts: u32,
+	connected_once: bool,
+	closed: bool,
+}
+
+impl<'u> ReconnectingClient<'u, TcpStream> {
+	/// Creates a client connecting with `ClientBuilder::connect_insecure`.
+	pub fn new(builder: ClientBuilder<'u>) -> Self {
+		ReconnectingClient::with_connect(builder, |builder| builder.connect_insecure())
+	}
+}
+
+impl<'u, S> ReconnectingClient<'u, S>
+where
+	S: Stream,
+{
+	/// Creates a client connecting with the given function, for example
+	/// `|builder| builder.connect(None)` to also connect to `wss://` urls.
+	pub fn with_connect<F>(builder: ClientBuilder<'u>, connect: F) -> Self
+	where
+		F: FnMut(&mut ClientBuilder<'u>) -> WebSocketResult<Client<S>> + Send + 'static,
+	{
+		ReconnectingClient {
+			builder,
+			connect: Box::new(connect),
+			client: None,
+			on_reconnect: None,
+			initial_backoff: Duration::from_millis(100),
+			max_backoff: Duration::from_secs(30),
+			max_attempts: 5,
+			connected_once: false,
+			closed: false,
+		}
+	}
+
+	/// Sets the time waited after the first failed attempt to reconnect,
+	/// doubled after every further failure up to `max`, which also caps the
+	/// first wait. This is 100 milliseconds up to 30 seconds by default.
+	pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+		self.initial_backoff = initial;
+		self.max_backoff = max;
+		self
+	}
+
+	/// Sets how many attempts are made to connect before giving up,
+	/// 5 by default.
+	pub fn max_attempts(mut self, attempts: u32) -> Self {
+		self.max_attempts = attempts;
+		self
+	}
+
+	/// Calls `callback` with the new client every time the client reconnects,
+	/// but not for the first connection.
+	pub fn on_reconnect<F>(mut self, callback: F) -> Self
+	where
+		F: FnMut(&mut Client<S>) + Send + 'static,
+	{
+		self.on_reconnect = Some(Box::new(callback));
+		self
+	}
+
+	/// The current connection, if there is one.
+	pub fn client_mut(&mut self) -> Option<&mut Client<S>> {
+		self.client.as_mut()
+	}
+
+	/// Sends a single message, reconnecting and sending it again if the
+	/// connection dropped.
+	///
+	/// A message that was written just before the connection dropped may be
+	/// lost without an error, as the loss is only noticed by a later send
+	/// or receive. Use `close` rather than sending a `Close` message, so
+	/// that the client knows not to reconnect.
+	pub fn send_message<M>(&mut self, message: &M) -> WebSocketResult<()>
+	where
+		M: ws::Message,
+	{
+		loop {
+			let result = self.connected()?.send_message(message);
+			match result {
+				Err(ref e) if self.reconnects_after(e) => self.client = None,
+				result => return result,
+			}
+		}
+	}
+
+	/// Reads a single message, reconnecting and reading from the new
+	/// connection if the connection dropped.
+	pub fn recv_message(&mut self) -> WebSocketResult<OwnedMessage> {
+		loop {
+			let result = self.connected()?.recv_message();
+			match result {
+				Err(ref e) if self.reconnects_after(e) => self.client = None,
+				Ok(OwnedMessage::Close(data)) => {
+					self.closed = true;
+					return Ok(OwnedMessage::Close(data));
+				}
+				result => return result,
+			}
+		}
+	}
+
+	/// Closes the connection with `Client::close`, after which the client
+	/// never reconnects. Does nothing if there is no connection.
+	pub fn close(
+		&mut self,
+		code: Option<CloseStatusCode>,
+		reason: Option<String>,
+	) -> WebSocketResult<()>
+	where
+		S: AsTcpStream,
+	{
+		self.closed = true;
+		match self.client {
+			Some(ref mut client) => client.close(code, reason),
+			None => Ok(()),
+		}
+	}
+
+	/// Whether `error` means the connection dropped and should be made again.
+	fn reconnects_after(&self, error: &WebSocketError) -> bool {
+		if self.closed {
+			return false;
+		}
+		match *error {
+			WebSocketError::NoDataAvailable => true,
+			WebSocketError::IoError(ref e) => matches!(
+				e.kind(),
+				ErrorKind::ConnectionReset
+					| ErrorKind::ConnectionAborted
+					| ErrorKind::BrokenPipe
+					| ErrorKind::NotConnected
+					| ErrorKind::UnexpectedEof
+			),
+			_ => false,
+		}
+	}
+
+	/// The current client, connecting first if there is none.
+	fn connected(&mut self) -> WebSocketResult<&mut Client<S>> {
+		if self.client.is_none() {
+			let mut client = self.connect_with_backoff()?;
+			if self.connected_once {
+				if let Some(ref mut callback) = self.on_reconnect {
+					callback(&mut client);
+				}
+			}
+			self.connected_once = true;
+			self.client = Some(client);
+		}
+		match self.client {
+			Some(ref mut client) => Ok(client),
+			None => unreachable!(),
+		}
+	}
+
+	fn connect_with_backoff(&mut self) -> WebSocketResult<Client<S>> {
+		let mut backoff = ::std::cmp::min(self.initial_backoff, self.max_backoff);
+		let mut attempt = 1;
+		loop {
+			match (self.connect)(&mut self.builder) {
+				Ok(client) => return Ok(client),
+				Err(e) => {
+					if attempt >= self.max_attempts {
+						return Err(e);
+					}
+				}
+			}
+			thread::sleep(backoff);
+			backoff = ::std::cmp::min(backoff * 2, self.max_backoff);
+			attempt += 1;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use message::Message;
+	use server::sync::Server;
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::sync::Arc;
+
+	#[test]
+	fn reconnects_after_the_server_drops() {
+		let mut server = Server::bind("127.0.0.1:0").unwrap();
+		let url = format!("ws://{}", server.local_addr().unwrap());
+		let server = thread::spawn(move || {
+			// the first connection is dropped without a close
+			let mut client = server.accept().ok().unwrap().accept().ok().unwrap();
+			let message = client.recv_message().unwrap();
+			assert_eq!(message, OwnedMessage::Text("one".to_string()));
+			client.shutdown().unwrap();
+			drop(client);
+
+			let mut client = server.accept().ok().unwrap().accept().ok().unwrap();
+			client.send_message(&Message::text("welcome back")).unwrap();
+			let message = client.recv_message().unwrap();
+			assert_eq!(message, OwnedMessage::Text("two".to_string()));
+			client.close(None, None).unwrap();
+		});
+
+		let reconnects = Arc::new(AtomicUsize::new(0));
+		let counter = reconnects.clone();
+		let mut client = ReconnectingClient::new(ClientBuilder::new(&url).unwrap())
+			.backoff(Duration::from_millis(10), Duration::from_millis(100))
+			.on_reconnect(move |_| {
+				counter.fetch_add(1, Ordering::SeqCst);
+			});
+
+		client.send_message(&Message::text("one")).unwrap();
+		assert_eq!(
+			client.recv_message().unwrap(),
+			OwnedMessage::Text("welcome back".to_string())
+		);
+		client.send_message(&Message::text("two")).unwrap();
+		match client.recv_message().unwrap() {
+			OwnedMessage::Close(_) => {}
+			other => panic!("unexpected message {:?}", other),
+		}
+		client.close(None, None).unwrap();
+		// a clean close is not followed by a reconnect
+		assert!(client.recv_message().is_err());
+		assert_eq!(reconnects.load(Ordering::SeqCst), 1);
+		server.join().unwrap();
+	}
+
+	#[test]
+	fn first_backoff_is_capped() {
+		use result::WebSocketError;
+		use std::time::Instant;
+
+		let builder = ClientBuilder::new("ws://127.0.0.1:1").unwrap();
+		let mut client: ReconnectingClient<TcpStream> =
+			ReconnectingClient::with_connect(builder, |_| {
+				Err(WebSocketError::ProtocolError("refused"))
+			})
+			.backoff(Duration::from_secs(60), Duration::from_millis(10))
+			.max_attempts(2);
+
+		let start = Instant::now();
+		assert!(client.send_message(&Message::text("never sent")).is_err());
+		assert!(start.elapsed() < Duration::from_secs(5));
+	}
+}
@@ -280,7 +280,7 @@ mod tests {
 			.and_then(|s| s.into_future().map_err(|(e, _)| e))
 			.and_then(|(m, _)| match m {
 				Some(ref m) if StatusCode::from_u16(m.subject.0) == StatusCode::NotFound => Ok(()),
-				_ => Err(io::Error::new(io::ErrorKind::Other, "test failed").into()),
+				_ => Err(io::Error::other("test failed").into()),
 			});
 		runtime.block_on(f).unwrap();
 	}
@@ -303,7 +303,7 @@ mod tests {
 			.map_err(|(e, _)| e)
 			.and_then(|(m, s)| match m {
 				Some(ref m) if m.subject.0 == Method::Get => Ok(s),
-				_ => Err(io::Error::new(io::ErrorKind::Other, "test failed").into()),
+				_ => Err(io::Error::other("test failed").into()),
 			})
 			.and_then(|s| {
 				s.send(Incoming {
@@ -1,6 +1,7 @@
 //! Module containing the default implementation of data frames.
 use result::{WebSocketError, WebSocketResult};
 use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
 use ws::dataframe::DataFrame as DataFrameable;
 use ws::util::header as dfh;
 use ws::util::header::DataFrameHeader;
@@ -87,7 +88,19 @@ impl DataFrame {
 		R: Read,
 	{
 		let header = dfh::read_header(reader)?;
+		DataFrame::read_dataframe_payload(reader, header, should_be_masked)
+	}
 
+	/// Reads the payload of a DataFrame whose header has already been read
+	/// from the Reader.
+	pub fn read_dataframe_payload<R>(
+		reader: &mut R,
+		header: DataFrameHeader,
+		should_be_masked: bool,
+	) -> WebSocketResult<Self>
+	where
+		R: Read,
+	{
 		let mut data: Vec<u8> = Vec::with_capacity(header.len as usize);
 		let read = reader.take(header.len).read_to_end(&mut data)?;
 		if (read as u64) < header.len {
@@ -131,6 +144,92 @@ impl DataFrameable for DataFrame {
 	}
 }
 
+/// Builds a `DataFrame` with any opcode and reserved bits, for example to
+/// send the frames of an extension with `Client::send_dataframe`.
+///
+/// The frame is finished, has no reserved bits set and an empty payload
+/// unless set otherwise.
+///
+/// ```rust
+/// use websocket::dataframe::{DataFrameBuilder, Opcode};
+///
+/// let frame = DataFrameBuilder::new(Opcode::Binary)
+///     .reserved([true, false, false])
+///     .data(b"compressed".to_vec())
+///     .build()
+///     .unwrap();
+/// assert!(frame.reserved[0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DataFrameBuilder {
+	frame: DataFrame,
+}
+
+impl DataFrameBuilder {
+	/// Starts building a frame with the given opcode.
+	pub fn new(opcode: Opcode) -> Self {
+		DataFrameBuilder {
+			frame: DataFrame::new(true, opcode, Vec::new()),
+		}
+	}
+
+	/// Sets whether this is the last frame of a message.
+	pub fn finished(mut self, finished: bool) -> Self {
+		self.frame.finished = finished;
+		self
+	}
+
+	/// Sets the three reserved bits, RSV1 first.
+	pub fn reserved(mut self, reserved: [bool; 3]) -> Self {
+		self.frame.reserved = reserved;
+		self
+	}
+
+	/// Sets the payload of the frame.
+	pub fn data(mut self, data: Vec<u8>) -> Self {
+		self.frame.data = data;
+		self
+	}
+
+	/// Builds the frame, failing with a `WebSocketError::ProtocolError` for
+	/// a control frame that is not finished or has a payload longer than
+	/// 125 bytes.
+	pub fn build(self) -> WebSocketResult<DataFrame> {
+		if self.frame.opcode as u8 >= 8 {
+			if !self.frame.finished {
+				return Err(WebSocketError::ProtocolError(
+					"Control frames cannot be fragmented",
+				));
+			}
+			if self.frame.data.len() > 125 {
+				return Err(WebSocketError::ProtocolError(
+					"Control frame payload too long, it can be at most 125 bytes",
+				));
+			}
+		}
+		Ok(self.frame)
+	}
+}
+
+/// The header of a data frame, as given to a `FrameObserver`.
+pub use ws::util::header::DataFrameHeader as FrameHeader;
+
+/// Whether a data frame given to a `FrameObserver` was sent or received.
+#[derive(Clone, Debug, Copy, PartialEq, Eq)]
+pub enum FrameDirection {
+	/// The frame was sent to the remote endpoint
+	Sent,
+	/// The frame was received from the remote endpoint
+	Received,
+}
+
+/// A function called with the header of every data frame sent or received.
+/// See `Client::set_frame_observer`.
+pub type FrameCallback = Box<dyn FnMut(FrameDirection, &FrameHeader) + Send>;
+
+/// A `FrameCallback` shared by a `Sender` and a `Receiver`.
+pub type FrameObserver = Arc<Mutex<FrameCallback>>;
+
 /// Represents a WebSocket data frame opcode
 #[derive(Clone, Debug, Copy, PartialEq)]
 pub enum Opcode {
@@ -0,0 +1,376 @@
+//! Support for the permessage-deflate extension
+//! ([RFC7692](https://tools.ietf.org/html/rfc7692)).
+//!
+//! To use the extension with a synchronous client offer it to the server with
+//! `ClientBuilder::add_extension`, if the server accepts it messages sent and
+//! received by the client will be compressed and decompressed transparently.
+//!
+//! ```rust,no_run
+//! # use websocket::ClientBuilder;
+//! use websocket::header::extensions::Extension;
+//!
+//! let client = ClientBuilder::new("ws://127.0.0.1:1234").unwrap()
+//!     .add_extension(Extension::new("permessage-deflate".to_string()))
+//!     .connect_insecure()
+//!     .unwrap();
+//!
+//! // check if the server accepted the extension
+//! let compressed = client.deflate().is_some();
+//! ```
+//!
+//! Only data messages (text and binary) are compressed, control frames and
+//! dataframes given to `send_dataframe` are sent as they are.
+//! Asynchronous clients do not support the extension yet.
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use header::extensions::Extension;
+use result::{WebSocketError, WebSocketResult};
+use std::cmp;
+
+/// The name of the extension as it appears in the `Sec-WebSocket-Extensions` header.
+pub const EXTENSION_NAME: &str = "permessage-deflate";
+
+/// The bytes removed from the end of every compressed message.
+const TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The largest LZ77 window size, and the default if none is negotiated.
+const MAX_WINDOW_BITS: u8 = 15;
+
+/// The parameters of a negotiated permessage-deflate extension.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeflateConfig {
+	/// The server resets its compression context after every message
+	pub server_no_context_takeover: bool,
+	/// The client resets its compression context after every message
+	pub client_no_context_takeover: bool,
+	/// The LZ77 window size (as a base-2 logarithm) used by the server
+	pub server_max_window_bits: u8,
+	/// The LZ77 window size (as a base-2 logarithm) used by the client
+	pub client_max_window_bits: u8,
+}
+
+impl Default for DeflateConfig {
+	fn default() -> Self {
+		DeflateConfig {
+			server_no_context_takeover: false,
+			client_no_context_takeover: false,
+			server_max_window_bits: MAX_WINDOW_BITS,
+			client_max_window_bits: MAX_WINDOW_BITS,
+		}
+	}
+}
+
+impl DeflateConfig {
+	/// Read the parameters of a permessage-deflate extension, usually the one
+	/// accepted by the server in its handshake response.
+	///
+	/// Unknown or repeated parameters and window sizes outside of 8 to 15 are
+	/// rejected, as required by the RFC.
+	pub fn from_extension(extension: &Extension) -> WebSocketResult<DeflateConfig> {
+		if extension.name != EXTENSION_NAME {
+			return Err(WebSocketError::ProtocolError(
+				"Extension is not permessage-deflate",
+			));
+		}
+
+		let mut config = DeflateConfig::default();
+		let mut seen = Vec::with_capacity(extension.params.len());
+		for param in &extension.params {
+			if seen.contains(&&param.name[..]) {
+				return Err(WebSocketError::ProtocolError(
+					"Repeated permessage-deflate parameter",
+				));
+			}
+			seen.push(&param.name[..]);
+
+			let value = param.value.as_ref().map(|v| v.trim_matches('"'));
+			match (&param.name[..], value) {
+				("server_no_context_takeover", None) => config.server_no_context_takeover = true,
+				("client_no_context_takeover", None) => config.client_no_context_takeover = true,
+				("server_max_window_bits", Some(bits)) => {
+					config.server_max_window_bits = parse_window_bits(bits)?
+				}
+				("client_max_window_bits", Some(bits)) => {
+					config.client_max_window_bits = parse_window_bits(bits)?
+				}
+				// a client may offer this without a value
+				("client_max_window_bits", None) => (),
+				_ => {
+					return Err(WebSocketError::ProtocolError(
+						"Invalid permessage-deflate parameter",
+					));
+				}
+			}
+		}
+		Ok(config)
+	}
+
+	/// Creates the compressor for messages sent by the client, if `client` is
+	/// true, or else for messages sent by the server.
+	pub fn deflater(&self, client: bool) -> WebSocketResult<Deflater> {
+		if client {
+			Deflater::new(self.client_max_window_bits, self.client_no_context_takeover)
+		} else {
+			Deflater::new(self.server_max_window_bits, self.server_no_context_takeover)
+		}
+	}
+
+	/// Creates the decompressor for messages received by the client, if `client`
+	/// is true, or else for messages received by the server.
+	pub fn inflater(&self, client: bool) -> Inflater {
+		if client {
+			Inflater::new(self.server_no_context_takeover)
+		} else {
+			Inflater::new(self.client_no_context_takeover)
+		}
+	}
+}
+
+fn parse_window_bits(bits: &str) -> WebSocketResult<u8> {
+	match bits.parse::<u8>() {
+		Ok(bits) if (8..=MAX_WINDOW_BITS).contains(&bits) => Ok(bits),
+		_ => Err(WebSocketError::ProtocolError(
+			"Invalid permessage-deflate window bits",
+		)),
+	}
+}
+
+/// Compresses the payloads of outgoing messages.
+pub struct Deflater {
+	compress: Compress,
+	no_context_takeover: bool,
+}
+
+impl Deflater {
+	/// Create a compressor using a window of `2^window_bits` bytes, which
+	/// resets its context after every message if `no_context_takeover` is set.
+	///
+	/// A window size of 8 cannot be produced by the underlying deflate
+	/// implementation, so it results in an error.
+	pub fn new(window_bits: u8, no_context_takeover: bool) -> WebSocketResult<Deflater> {
+		if !(9..=MAX_WINDOW_BITS).contains(&window_bits) {
+			return Err(WebSocketError::ProtocolError(
+				"Unsupported permessage-deflate window bits",
+			));
+		}
+		Ok(Deflater {
+			compress: Compress::new_with_window_bits(Compression::default(), false, window_bits),
+			no_context_takeover,
+		})
+	}
+
+	/// Compress the payload of a single message.
+	pub fn compress(&mut self, data: &[u8]) -> WebSocketResult<Vec<u8>> {
+		let start = self.compress.total_in();
+		let mut output = Vec::with_capacity(data.len() / 2 + 64);
+
+		loop {
+			let consumed = (self.compress.total_in() - start) as usize;
+			self.compress
+				.compress_vec(&data[consumed..], &mut output, FlushCompress::Sync)
+				.map_err(|_| WebSocketError::DataFrameError("Failed to compress message"))?;
+
+			let consumed = (self.compress.total_in() - start) as usize;
+			// the flush is complete once there is room left in the output
+			if consumed == data.len() && output.len() < output.capacity() {
+				break;
+			}
+			let len = output.len();
+			output.reserve(len + 64);
+		}
+
+		if output.ends_with(&TRAILER) {
+			let len = output.len() - TRAILER.len();
+			output.truncate(len);
+		}
+		if self.no_context_takeover {
+			self.compress.reset();
+		}
+		Ok(output)
+	}
+}
+
+/// Decompresses the payloads of incoming messages.
+pub struct Inflater {
+	decompress: Decompress,
+	no_context_takeover: bool,
+}
+
+impl Inflater {
+	/// Create a decompressor, which resets its context after every message if
+	/// `no_context_takeover` is set.
+	///
+	/// The largest window size is always used so that any window negotiated
+	/// for the peer can be decompressed.
+	pub fn new(no_context_takeover: bool) -> Inflater {
+		Inflater {
+			decompress: Decompress::new_with_window_bits(false, MAX_WINDOW_BITS),
+			no_context_takeover,
+		}
+	}
+
+	/// Decompress the payload of a single message.
+	///
+	/// Decompressing stops with a `WebSocketError::MessageTooBig` as soon as
+	/// the payload grows larger than `max` bytes, so that a small message
+	/// which decompresses to a huge payload is never held in memory. The
+	/// context is reset in that case, so the connection should be closed.
+	pub fn decompress(&mut self, data: &[u8], max: Option<usize>) -> WebSocketResult<Vec<u8>> {
+		let mut output = Vec::new();
+		self.decompress_into(data, &mut output, max)?;
+		Ok(output)
+	}
+
+	/// Decompress the payload of a single message into `output`, like
+	/// `decompress`. The output is cleared first and its memory is reused.
+	pub fn decompress_into(
+		&mut self,
+		data: &[u8],
+		output: &mut Vec<u8>,
+		max: Option<usize>,
+	) -> WebSocketResult<()> {
+		let mut input = Vec::with_capacity(data.len() + TRAILER.len());
+		input.extend_from_slice(data);
+		input.extend_from_slice(&TRAILER);
+
+		let start = self.decompress.total_in();
+		output.clear();
+		output.reserve(limit_growth(0, data.len() * 2 + 64, max));
+
+		loop {
+			let consumed = (self.decompress.total_in() - start) as usize;
+			let produced = output.len();
+			let status = self
+				.decompress
+				.decompress_vec(&input[consumed..], output, FlushDecompress::Sync)
+				.map_err(|_| WebSocketError::ProtocolError("Invalid compressed message"))?;
+
+			if let Some(max) = max {
+				if output.len() > max {
+					self.decompress.reset(false);
+					output.clear();
+					return Err(WebSocketError::MessageTooBig);
+				}
+			}
+			let now_consumed = (self.decompress.total_in() - start) as usize;
+			if status == Status::StreamEnd
+				|| (now_consumed == input.len() && output.len() < output.capacity())
+			{
+				break;
+			}
+			if now_consumed == consumed
+				&& output.len() == produced
+				&& output.len() < output.capacity()
+			{
+				return Err(WebSocketError::ProtocolError("Invalid compressed message"));
+			}
+			let len = output.len();
+			output.reserve(limit_growth(len, len + 64, max));
+		}
+
+		if self.no_context_takeover {
+			self.decompress.reset(false);
+		}
+		Ok(())
+	}
+}
+
+/// How much to grow an output of `len` bytes by, at most one byte past `max`
+/// so that going over the limit is noticed without buffering much more.
+fn limit_growth(len: usize, additional: usize, max: Option<usize>) -> usize {
+	match max {
+		Some(max) => cmp::min(additional, max.saturating_sub(len) + 1),
+		None => additional,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use header::extensions::Parameter;
+
+	#[test]
+	fn round_trip_with_context_takeover() {
+		let text = "a large and very repetitive text message. ".repeat(1000);
+		let mut deflater = Deflater::new(15, false).unwrap();
+		let mut inflater = Inflater::new(false);
+
+		for _ in 0..2 {
+			let compressed = deflater.compress(text.as_bytes()).unwrap();
+			assert!(compressed.len() < text.len() / 10);
+			assert!(!compressed.ends_with(&TRAILER));
+			assert_eq!(
+				inflater.decompress(&compressed, None).unwrap(),
+				text.as_bytes()
+			);
+		}
+	}
+
+	#[test]
+	fn round_trip_without_context_takeover() {
+		let mut deflater = Deflater::new(10, true).unwrap();
+		let mut inflater = Inflater::new(true);
+
+		let first = deflater.compress(b"Hello").unwrap();
+		let second = deflater.compress(b"Hello").unwrap();
+		// a fresh context compresses the same message the same way
+		assert_eq!(first, second);
+		assert_eq!(inflater.decompress(&first, None).unwrap(), b"Hello");
+		assert_eq!(inflater.decompress(&second, None).unwrap(), b"Hello");
+		assert_eq!(
+			inflater
+				.decompress(&deflater.compress(b"").unwrap(), None)
+				.unwrap(),
+			b""
+		);
+	}
+
+	#[test]
+	fn decompress_rfc_example() {
+		// "Hello" compressed as shown in RFC7692 section 7.2.3.1
+		let mut inflater = Inflater::new(false);
+		let data = [0xf2, 0x48, 0xcd, 0xc9, 0xc9, 0x07, 0x00];
+		assert_eq!(inflater.decompress(&data, None).unwrap(), b"Hello");
+	}
+
+	#[test]
+	fn decompress_stops_at_the_limit() {
+		let mut deflater = Deflater::new(15, false).unwrap();
+		let bomb = deflater.compress(&vec![0; 16 * 1024 * 1024]).unwrap();
+		assert!(bomb.len() < 64 * 1024);
+
+		let mut inflater = Inflater::new(false);
+		match inflater.decompress(&bomb, Some(1024)) {
+			Err(WebSocketError::MessageTooBig) => {}
+			Err(e) => panic!("unexpected error {:?}", e),
+			Ok(payload) => panic!("decompressed {} bytes", payload.len()),
+		}
+
+		let mut inflater = Inflater::new(false);
+		let payload = inflater.decompress(&bomb, Some(16 * 1024 * 1024)).unwrap();
+		assert_eq!(payload.len(), 16 * 1024 * 1024);
+	}
+
+	#[test]
+	fn config_from_extension() {
+		let extension = Extension {
+			name: EXTENSION_NAME.to_string(),
+			params: vec![
+				Parameter::new("server_no_context_takeover".to_string(), None),
+				Parameter::new("client_max_window_bits".to_string(), Some("10".to_string())),
+			],
+		};
+		let config = DeflateConfig::from_extension(&extension).unwrap();
+		assert!(config.server_no_context_takeover);
+		assert!(!config.client_no_context_takeover);
+		assert_eq!(config.client_max_window_bits, 10);
+		assert_eq!(config.server_max_window_bits, 15);
+
+		let extension: Extension = "permessage-deflate; server_max_window_bits=16"
+			.parse()
+			.unwrap();
+		assert!(DeflateConfig::from_extension(&extension).is_err());
+
+		let extension: Extension = "permessage-deflate; bogus".parse().unwrap();
+		assert!(DeflateConfig::from_extension(&extension).is_err());
+	}
+}
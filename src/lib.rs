@@ -35,6 +35,8 @@ extern crate base64;
 extern crate byteorder;
 #[cfg(feature = "async")]
 extern crate bytes;
+#[cfg(feature = "deflate")]
+extern crate flate2;
 #[cfg(feature = "async")]
 pub extern crate futures;
 extern crate hyper;
@@ -42,6 +44,8 @@ extern crate hyper;
 extern crate native_tls;
 extern crate rand;
 extern crate sha1;
+#[cfg(feature = "sync")]
+extern crate socket2;
 #[cfg(feature = "async")]
 extern crate tokio;
 #[cfg(feature = "async-ssl")]
@@ -76,6 +80,9 @@ pub mod ws;
 #[cfg(feature = "async")]
 pub mod codec;
 
+#[cfg(feature = "deflate")]
+pub mod deflate;
+
 #[cfg(feature = "sync")]
 pub mod receiver;
 #[cfg(feature = "sync")]
@@ -109,6 +116,8 @@ pub mod sync {
 	/// A collection of handy synchronous-only parts of the `client` module.
 	pub mod client {
 		pub use client::builder::ClientBuilder;
+		pub use client::pool::ClientPool;
+		pub use client::reconnect::ReconnectingClient;
 		pub use client::sync::*;
 	}
 	pub use client::sync::Client;
@@ -152,6 +161,7 @@ pub mod async {
 
 pub use self::client::builder::ClientBuilder;
 pub use self::message::CloseData;
+pub use self::message::CloseStatusCode;
 pub use self::message::Message;
 pub use self::message::OwnedMessage;
 
@@ -27,6 +27,56 @@ pub enum Type {
 	Close = 8,
 }
 
+/// A set of message types, used to pick the messages to receive with
+/// `Client::incoming_messages_filtered`. Close messages always pass a filter.
+///
+///```rust
+///# use websocket::message::{MessageTypeFilter, Type};
+///# use websocket::OwnedMessage;
+///let filter = MessageTypeFilter::only(Type::Text);
+///assert!(filter.matches(&OwnedMessage::Text("hi".to_string())));
+///assert!(!filter.matches(&OwnedMessage::Ping(vec![])));
+///assert!(filter.matches(&OwnedMessage::Close(None)));
+///```
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct MessageTypeFilter(u16);
+
+impl MessageTypeFilter {
+	/// A filter passing every message.
+	pub fn all() -> Self {
+		MessageTypeFilter::only(Type::Text)
+			.with(Type::Binary)
+			.with(Type::Ping)
+			.with(Type::Pong)
+	}
+
+	/// A filter passing messages of the given type, and close messages.
+	pub fn only(kind: Type) -> Self {
+		MessageTypeFilter(1 << Type::Close as u16).with(kind)
+	}
+
+	/// Also passes messages of the given type.
+	pub fn with(self, kind: Type) -> Self {
+		MessageTypeFilter(self.0 | 1 << kind as u16)
+	}
+
+	/// Checks if messages of the given type pass this filter.
+	pub fn contains(&self, kind: Type) -> bool {
+		self.0 & 1 << kind as u16 != 0
+	}
+
+	/// Checks if the given message passes this filter.
+	pub fn matches(&self, message: &OwnedMessage) -> bool {
+		self.contains(match *message {
+			OwnedMessage::Text(_) => Type::Text,
+			OwnedMessage::Binary(_) => Type::Binary,
+			OwnedMessage::Close(_) => Type::Close,
+			OwnedMessage::Ping(_) => Type::Ping,
+			OwnedMessage::Pong(_) => Type::Pong,
+		})
+	}
+}
+
 /// Represents a WebSocket message.
 ///
 /// This message also has the ability to not own its payload, and stores its entire payload in
@@ -242,6 +292,26 @@ impl<'a> ws::Message for Message<'a> {
 		};
 		Ok(msg)
 	}
+
+	fn is_close(&self) -> bool {
+		self.opcode == Type::Close
+	}
+
+	fn ping_payload(&self) -> Option<&[u8]> {
+		if self.opcode == Type::Ping {
+			Some(&self.payload)
+		} else {
+			None
+		}
+	}
+
+	fn control_size(&self) -> Option<usize> {
+		if DataFrameTrait::opcode(self) >= 8 {
+			Some(self.size())
+		} else {
+			None
+		}
+	}
 }
 
 /// Represents an owned WebSocket message.
@@ -362,6 +432,25 @@ impl ws::Message for OwnedMessage {
 	{
 		Ok(Message::from_dataframes(frames)?.into())
 	}
+
+	fn is_close(&self) -> bool {
+		OwnedMessage::is_close(self)
+	}
+
+	fn ping_payload(&self) -> Option<&[u8]> {
+		match *self {
+			OwnedMessage::Ping(ref data) => Some(data),
+			_ => None,
+		}
+	}
+
+	fn control_size(&self) -> Option<usize> {
+		if self.is_control() {
+			Some(self.size())
+		} else {
+			None
+		}
+	}
 }
 
 impl ws::dataframe::DataFrame for OwnedMessage {
@@ -489,6 +578,10 @@ impl CloseData {
 			reason,
 		}
 	}
+	/// The status-code of the CloseData as a `CloseStatusCode`
+	pub fn status_code(&self) -> CloseStatusCode {
+		CloseStatusCode::from_u16(self.status_code)
+	}
 	/// Convert this into a vector of bytes
 	pub fn into_bytes(self) -> io::Result<Vec<u8>> {
 		let mut buf = Vec::new();
@@ -500,6 +593,106 @@ impl CloseData {
 	}
 }
 
+/// The status codes of Close messages, as registered by
+/// [RFC6455](https://tools.ietf.org/html/rfc6455#section-7.4) and IANA
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum CloseStatusCode {
+	/// 1000, the purpose of the connection has been fulfilled
+	NormalClosure,
+	/// 1001, an endpoint is going away, like a server going down or a browser
+	/// navigating away from a page
+	GoingAway,
+	/// 1002, the connection is closed due to a protocol error
+	ProtocolError,
+	/// 1003, an endpoint received a type of data it cannot accept
+	UnsupportedData,
+	/// 1005, no status code was present, this must not be sent in a Close message
+	NoStatusReceived,
+	/// 1006, the connection was closed abnormally without a Close message,
+	/// this must not be sent in a Close message
+	AbnormalClosure,
+	/// 1007, a message contained data that was not consistent with its type,
+	/// like non-UTF-8 data in a text message
+	InvalidPayload,
+	/// 1008, a message violated the policy of an endpoint
+	PolicyViolation,
+	/// 1009, a message was too big to process
+	MessageTooBig,
+	/// 1010, the server did not negotiate an extension required by the client
+	MandatoryExtension,
+	/// 1011, the server encountered an unexpected condition
+	InternalError,
+	/// 1012, the server is restarting
+	ServiceRestart,
+	/// 1013, the server is overloaded and the client should try again later
+	TryAgainLater,
+	/// 1014, a gateway received an invalid response from the upstream server
+	BadGateway,
+	/// 1015, the TLS handshake failed, this must not be sent in a Close message
+	TlsHandshake,
+	/// Any other status code, like the ones reserved for libraries (3000-3999)
+	/// and applications (4000-4999)
+	Other(u16),
+}
+
+impl CloseStatusCode {
+	/// Get the status code represented by a number
+	pub fn from_u16(code: u16) -> CloseStatusCode {
+		match code {
+			1000 => CloseStatusCode::NormalClosure,
+			1001 => CloseStatusCode::GoingAway,
+			1002 => CloseStatusCode::ProtocolError,
+			1003 => CloseStatusCode::UnsupportedData,
+			1005 => CloseStatusCode::NoStatusReceived,
+			1006 => CloseStatusCode::AbnormalClosure,
+			1007 => CloseStatusCode::InvalidPayload,
+			1008 => CloseStatusCode::PolicyViolation,
+			1009 => CloseStatusCode::MessageTooBig,
+			1010 => CloseStatusCode::MandatoryExtension,
+			1011 => CloseStatusCode::InternalError,
+			1012 => CloseStatusCode::ServiceRestart,
+			1013 => CloseStatusCode::TryAgainLater,
+			1014 => CloseStatusCode::BadGateway,
+			1015 => CloseStatusCode::TlsHandshake,
+			code => CloseStatusCode::Other(code),
+		}
+	}
+
+	/// Get the number sent on the wire for this status code
+	pub fn to_u16(self) -> u16 {
+		match self {
+			CloseStatusCode::NormalClosure => 1000,
+			CloseStatusCode::GoingAway => 1001,
+			CloseStatusCode::ProtocolError => 1002,
+			CloseStatusCode::UnsupportedData => 1003,
+			CloseStatusCode::NoStatusReceived => 1005,
+			CloseStatusCode::AbnormalClosure => 1006,
+			CloseStatusCode::InvalidPayload => 1007,
+			CloseStatusCode::PolicyViolation => 1008,
+			CloseStatusCode::MessageTooBig => 1009,
+			CloseStatusCode::MandatoryExtension => 1010,
+			CloseStatusCode::InternalError => 1011,
+			CloseStatusCode::ServiceRestart => 1012,
+			CloseStatusCode::TryAgainLater => 1013,
+			CloseStatusCode::BadGateway => 1014,
+			CloseStatusCode::TlsHandshake => 1015,
+			CloseStatusCode::Other(code) => code,
+		}
+	}
+}
+
+impl From<u16> for CloseStatusCode {
+	fn from(code: u16) -> Self {
+		CloseStatusCode::from_u16(code)
+	}
+}
+
+impl From<CloseStatusCode> for u16 {
+	fn from(code: CloseStatusCode) -> Self {
+		code.to_u16()
+	}
+}
+
 /// Trait representing the ability to convert
 /// self to a `Cow<'a, [u8]>`
 pub trait IntoCowBytes<'a> {
@@ -524,3 +717,51 @@ impl<'a> IntoCowBytes<'a> for Cow<'a, [u8]> {
 		self
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn close_status_code_round_trip() {
+		for code in 1000..1016 {
+			let status = CloseStatusCode::from_u16(code);
+			assert_eq!(status.to_u16(), code);
+			if code != 1004 {
+				assert!(status != CloseStatusCode::Other(code));
+			}
+		}
+		assert_eq!(CloseStatusCode::from_u16(1001), CloseStatusCode::GoingAway);
+		assert_eq!(
+			CloseStatusCode::from_u16(1004),
+			CloseStatusCode::Other(1004)
+		);
+		assert_eq!(
+			CloseStatusCode::from_u16(4000),
+			CloseStatusCode::Other(4000)
+		);
+		assert_eq!(u16::from(CloseStatusCode::Other(4000)), 4000);
+	}
+
+	#[test]
+	fn close_data_with_status_code() {
+		let data = CloseData::new(CloseStatusCode::GoingAway.to_u16(), "bye".to_string());
+		assert_eq!(data.status_code, 1001);
+		assert_eq!(data.status_code(), CloseStatusCode::GoingAway);
+		assert_eq!(
+			CloseData::new(4321, String::new()).status_code(),
+			CloseStatusCode::Other(4321)
+		);
+
+		let message = OwnedMessage::Close(Some(data));
+		let mut bytes = Vec::new();
+		ws::Message::serialize(&message, &mut bytes, false).unwrap();
+		let frame = ::dataframe::DataFrame::read_dataframe(&mut &bytes[..], false).unwrap();
+		match <OwnedMessage as ws::Message>::from_dataframes(vec![frame]).unwrap() {
+			OwnedMessage::Close(Some(data)) => {
+				assert_eq!(data.status_code(), CloseStatusCode::GoingAway)
+			}
+			other => panic!("unexpected message {:?}", other),
+		}
+	}
+}
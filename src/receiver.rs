@@ -4,15 +4,22 @@ use std::io::Read;
 use std::io::Result as IoResult;
 
 use hyper::buffer::BufReader;
+use hyper::header::Headers;
 
-use dataframe::{DataFrame, Opcode};
-use message::OwnedMessage;
+use dataframe::{DataFrame, FrameDirection, FrameHeader, FrameObserver, Opcode};
+#[cfg(feature = "deflate")]
+use deflate::{DeflateConfig, Inflater};
+use message::{CloseData, OwnedMessage, Type};
 use result::{WebSocketError, WebSocketResult};
+use sender::PingTimes;
+use std::str::from_utf8;
+use std::time::Duration;
 pub use stream::sync::Shutdown;
 use stream::sync::{AsTcpStream, Stream};
 use ws;
 use ws::receiver::Receiver as ReceiverTrait;
 use ws::receiver::{DataFrameIterator, MessageIterator};
+use ws::util::header as dfh;
 
 /// This reader bundles an existing stream with a parsing algorithm.
 /// It is used by the client in its `.split()` function as the reading component.
@@ -24,12 +31,76 @@ where
 	pub stream: BufReader<R>,
 	/// the parser to parse bytes into messages
 	pub receiver: Receiver,
+	// the state of the split client, restored by `Client::from_split`
+	headers: Headers,
+	auto_pong: bool,
+	close_received: bool,
+	#[cfg(feature = "deflate")]
+	deflate: Option<DeflateConfig>,
 }
 
 impl<R> Reader<R>
 where
 	R: Read,
 {
+	/// Bundles a stream with a receiver, for a connection without a handshake.
+	pub fn new(stream: BufReader<R>, receiver: Receiver) -> Self {
+		Reader {
+			stream,
+			receiver,
+			headers: Headers::new(),
+			auto_pong: false,
+			close_received: false,
+			#[cfg(feature = "deflate")]
+			deflate: None,
+		}
+	}
+
+	#[doc(hidden)]
+	pub fn with_handshake(mut self, headers: Headers, close_received: bool) -> Self {
+		self.headers = headers;
+		self.close_received = close_received;
+		self
+	}
+
+	#[cfg(feature = "deflate")]
+	#[doc(hidden)]
+	pub fn with_deflate(mut self, config: Option<DeflateConfig>) -> Self {
+		self.deflate = config;
+		self
+	}
+
+	/// The headers of the handshake response of the split client, empty for
+	/// a reader made with `new`.
+	pub fn headers(&self) -> &Headers {
+		&self.headers
+	}
+
+	/// Sets whether the client joined from this reader by `Client::from_split`
+	/// answers pings, see `Client::set_auto_pong`. The reader itself never
+	/// answers pings, as it cannot send.
+	pub fn set_auto_pong(&mut self, auto_pong: bool) {
+		self.auto_pong = auto_pong;
+	}
+
+	/// Whether the client joined from this reader answers pings.
+	pub fn auto_pong(&self) -> bool {
+		self.auto_pong
+	}
+
+	/// Whether a close message was received, before the split or through
+	/// `recv_message` or `recv_message_into` of this reader.
+	pub fn is_close_received(&self) -> bool {
+		self.close_received
+	}
+
+	/// The parameters of the permessage-deflate extension of the split
+	/// client, if it is in use.
+	#[cfg(feature = "deflate")]
+	pub fn deflate(&self) -> Option<&DeflateConfig> {
+		self.deflate.as_ref()
+	}
+
 	/// Reads a single data frame from the remote endpoint.
 	pub fn recv_dataframe(&mut self) -> WebSocketResult<DataFrame> {
 		self.receiver.recv_dataframe(&mut self.stream)
@@ -42,14 +113,92 @@ where
 
 	/// Reads a single message from this receiver.
 	pub fn recv_message(&mut self) -> WebSocketResult<OwnedMessage> {
-		self.receiver.recv_message(&mut self.stream)
+		let message = self.receiver.recv_message(&mut self.stream)?;
+		if message.is_close() {
+			self.close_received = true;
+		}
+		Ok(message)
+	}
+
+	/// Reads the payload of a single message into `buf`, returning the type of
+	/// the message. See `Receiver::recv_message_into`.
+	pub fn recv_message_into(&mut self, buf: &mut Vec<u8>) -> WebSocketResult<Type> {
+		let message = self.receiver.recv_message_into(&mut self.stream, buf)?;
+		if message == Type::Close {
+			self.close_received = true;
+		}
+		Ok(message)
 	}
 
 	/// An iterator over incoming messsages.
 	/// This iterator will block until new messages arrive and will never halt.
+	/// Unlike `recv_message`, it does not notice a close for
+	/// `is_close_received`.
 	pub fn incoming_messages<'a>(&'a mut self) -> MessageIterator<'a, Receiver, BufReader<R>> {
 		self.receiver.incoming_messages(&mut self.stream)
 	}
+
+	/// An iterator over incoming messages, like `incoming_messages`, which
+	/// gives every `Pong` along with the time since the `Ping` it answers
+	/// was sent.
+	///
+	/// The round trip time is only known for pongs answering a ping sent
+	/// through the `Writer` of the same client, as the halves made by
+	/// `Client::split` share a `PingTimes` table. It is `None` for all
+	/// other messages.
+	///
+	///```no_run
+	///# extern crate websocket;
+	///# fn main() {
+	///use websocket::{ClientBuilder, Message, OwnedMessage};
+	///
+	///let client = ClientBuilder::new("ws://127.0.0.1:1234").unwrap()
+	///                     .connect_insecure().unwrap();
+	///let (mut receiver, mut sender) = client.split().unwrap();
+	///
+	///sender.send_message(&Message::ping(b"hi".to_vec())).unwrap();
+	///for message in receiver.incoming_messages_with_rtt() {
+	///    if let (OwnedMessage::Pong(_), Some(rtt)) = message.unwrap() {
+	///        println!("round trip took {:?}", rtt);
+	///    }
+	///}
+	///# }
+	///```
+	pub fn incoming_messages_with_rtt<'a>(&'a mut self) -> RttMessageIterator<'a, R> {
+		RttMessageIterator { reader: self }
+	}
+}
+
+/// An iterator over incoming messages annotated with the round trip time of
+/// pongs, see `Reader::incoming_messages_with_rtt`.
+pub struct RttMessageIterator<'a, R>
+where
+	R: 'a + Read,
+{
+	reader: &'a mut Reader<R>,
+}
+
+impl<'a, R> Iterator for RttMessageIterator<'a, R>
+where
+	R: Read,
+{
+	type Item = WebSocketResult<(OwnedMessage, Option<Duration>)>;
+
+	/// Get the next message from the reader. Always returns `Some`.
+	fn next(&mut self) -> Option<Self::Item> {
+		let message = match self.reader.recv_message() {
+			Ok(message) => message,
+			Err(e) => return Some(Err(e)),
+		};
+		let rtt = match message {
+			OwnedMessage::Pong(ref data) => match self.reader.receiver.ping_times {
+				Some(ref ping_times) => ping_times.pong_received(data),
+				None => None,
+			},
+			_ => None,
+		};
+		Some(Ok((message, rtt)))
+	}
 }
 
 impl<S> Reader<S>
@@ -58,10 +207,18 @@ where
 {
 	/// Closes the receiver side of the connection, will cause all pending and future IO to
 	/// return immediately with an appropriate value.
+	///
+	/// A reader blocked in a receive on another thread can be woken up by
+	/// `Writer::shutdown_all` on the writer of the same connection.
 	pub fn shutdown(&self) -> IoResult<()> {
 		self.stream.get_ref().as_tcp().shutdown(Shutdown::Read)
 	}
 
+	/// Sets the read timeout of the stream, see `Client::set_read_timeout`.
+	pub fn set_read_timeout(&self, dur: Option<Duration>) -> IoResult<()> {
+		self.stream.get_ref().as_tcp().set_read_timeout(dur)
+	}
+
 	/// Shuts down both Sender and Receiver, will cause all pending and future IO to
 	/// return immediately with an appropriate value.
 	pub fn shutdown_all(&self) -> IoResult<()> {
@@ -74,6 +231,17 @@ where
 pub struct Receiver {
 	buffer: Vec<DataFrame>,
 	mask: bool,
+	max_message_size: Option<usize>,
+	max_fragments: Option<usize>,
+	// fragments merged into the partial message kept by `recv_message_into`
+	buffered_fragments: usize,
+	#[cfg(feature = "deflate")]
+	inflater: Option<Inflater>,
+	// the compressed payload of the message being inflated, kept for its memory
+	#[cfg(feature = "deflate")]
+	compressed: Vec<u8>,
+	observer: Option<FrameObserver>,
+	ping_times: Option<PingTimes>,
 }
 
 impl Receiver {
@@ -82,8 +250,375 @@ impl Receiver {
 		Receiver {
 			buffer: Vec::new(),
 			mask,
+			max_message_size: None,
+			max_fragments: None,
+			buffered_fragments: 0,
+			#[cfg(feature = "deflate")]
+			inflater: None,
+			#[cfg(feature = "deflate")]
+			compressed: Vec::new(),
+			observer: None,
+			ping_times: None,
+		}
+	}
+
+	/// Looks up the round trip time of received pongs in `ping_times`, or
+	/// stops doing so when given `None`. See `Reader::incoming_messages_with_rtt`.
+	pub fn set_ping_times(&mut self, ping_times: Option<PingTimes>) {
+		self.ping_times = ping_times;
+	}
+
+	/// Calls `observer` with the header of every data frame received,
+	/// or stops calling it when given `None`.
+	pub fn set_frame_observer(&mut self, observer: Option<FrameObserver>) {
+		self.observer = observer;
+	}
+
+	/// Reads the header of a data frame, showing it to the frame observer.
+	fn read_header<R: Read>(&self, reader: &mut R) -> WebSocketResult<FrameHeader> {
+		let header = dfh::read_header(reader)?;
+		if let Some(ref observer) = self.observer {
+			let mut observer = observer.lock().unwrap_or_else(|e| e.into_inner());
+			(*observer)(FrameDirection::Received, &header);
+		}
+		Ok(header)
+	}
+
+	/// Sets the maximum size in bytes of the payload of a received message,
+	/// or removes the limit when given `None`.
+	///
+	/// The limit applies to the payloads of all the fragments of a message
+	/// together, and is checked before each payload is read, so a larger
+	/// message fails with `WebSocketError::MessageTooBig` without being
+	/// buffered. The rest of the message is left unread on the stream, so
+	/// the connection should be closed after this error.
+	///
+	/// There is no limit by default.
+	pub fn set_max_message_size(&mut self, max_message_size: Option<usize>) {
+		self.max_message_size = max_message_size;
+	}
+
+	/// The maximum size in bytes of the payload of a received message.
+	pub fn max_message_size(&self) -> Option<usize> {
+		self.max_message_size
+	}
+
+	/// Sets the maximum number of data frames a received message can be
+	/// fragmented into, or removes the limit when given `None`.
+	///
+	/// This is independent of the size of the message, and stops a remote
+	/// endpoint from keeping a message in progress forever by sending empty
+	/// continuation frames. A message with more fragments fails with a
+	/// `WebSocketError::ProtocolError`, and its fragments are dropped.
+	///
+	/// There is no limit by default.
+	pub fn set_max_fragments(&mut self, max_fragments: Option<usize>) {
+		self.max_fragments = max_fragments;
+	}
+
+	/// The maximum number of data frames a received message can be
+	/// fragmented into.
+	pub fn max_fragments(&self) -> Option<usize> {
+		self.max_fragments
+	}
+
+	/// Fails if a message would have more than the maximum number of fragments.
+	fn check_fragments(&self, fragments: usize) -> WebSocketResult<()> {
+		match self.max_fragments {
+			Some(max) if fragments > max => Err(WebSocketError::ProtocolError(
+				"Too many fragments in a message",
+			)),
+			_ => Ok(()),
+		}
+	}
+
+	/// Reads the payload of a single message into `buf`, returning the type
+	/// of the message.
+	///
+	/// The buffer is cleared first, and its memory is reused for the payload so
+	/// that receiving many messages does not need an allocation for each of
+	/// them. The payload of a close message is left as it was sent, the status
+	/// code in its first two bytes followed by the reason. A close payload
+	/// of a single byte, with a status code that must not be sent or with a
+	/// reason that is not UTF-8 is an error.
+	///
+	/// ```rust
+	/// # use websocket::Message;
+	/// # use websocket::ws::Message as MessageTrait;
+	/// use websocket::message::Type;
+	/// use websocket::receiver::Receiver;
+	///
+	/// # let mut data = Vec::new();
+	/// # Message::text("Hello").serialize(&mut data, false).unwrap();
+	/// let mut receiver = Receiver::new(false);
+	/// let mut buf = Vec::with_capacity(1024);
+	///
+	/// let message = receiver.recv_message_into(&mut &data[..], &mut buf).unwrap();
+	/// assert_eq!(message, Type::Text);
+	/// assert_eq!(buf, b"Hello");
+	/// ```
+	pub fn recv_message_into<R>(
+		&mut self,
+		reader: &mut R,
+		buf: &mut Vec<u8>,
+	) -> WebSocketResult<Type>
+	where
+		R: Read,
+	{
+		buf.clear();
+		// the start of a message interrupted by a control frame
+		let mut opcode = None;
+		let mut compressed = false;
+		let mut fragments = ::std::cmp::max(
+			self.buffer.len(),
+			::std::mem::take(&mut self.buffered_fragments),
+		);
+		for frame in self.buffer.drain(..) {
+			if opcode.is_none() {
+				opcode = Some(frame.opcode);
+				compressed = frame.reserved[0];
+			}
+			buf.extend_from_slice(&frame.data);
+		}
+
+		loop {
+			let (header, frame_opcode) = self.read_frame_header(reader)?;
+			match frame_opcode {
+				Opcode::Continuation if opcode.is_none() => {
+					return Err(WebSocketError::ProtocolError(
+						"Unexpected continuation data frame opcode",
+					));
+				}
+				Opcode::Text | Opcode::Binary if opcode.is_some() => {
+					return Err(WebSocketError::ProtocolError(
+						"Unexpected data frame opcode",
+					));
+				}
+				_ => {}
+			}
+			let control = header.opcode >= 8;
+
+			if !control {
+				fragments += 1;
+				if let Err(e) = self.check_fragments(fragments) {
+					buf.clear();
+					return Err(e);
+				}
+			}
+
+			let buffered = if control { 0 } else { buf.len() as u64 };
+			if let Some(max) = self.max_message_size {
+				if buffered + header.len > max as u64 {
+					buf.clear();
+					return Err(WebSocketError::MessageTooBig);
+				}
+			}
+
+			if let (true, Some(opcode)) = (control, opcode) {
+				// keep the start of the data message for the next call,
+				// unless it is abandoned because of a close
+				if frame_opcode != Opcode::Close {
+					let mut partial = DataFrame::new(false, opcode, buf.to_vec());
+					partial.reserved[0] = compressed;
+					self.buffer.push(partial);
+					self.buffered_fragments = fragments;
+				}
+				buf.clear();
+			}
+
+			let start = if control { 0 } else { buf.len() };
+			let read = reader.take(header.len).read_to_end(buf)?;
+			if (read as u64) < header.len {
+				return Err(::std::io::Error::new(
+					::std::io::ErrorKind::UnexpectedEof,
+					"incomplete payload",
+				)
+				.into());
+			}
+			if let Some(mask) = header.mask {
+				for (byte, key) in buf[start..].iter_mut().zip(mask.iter().cycle()) {
+					*byte ^= *key;
+				}
+			}
+
+			if control {
+				return match frame_opcode {
+					Opcode::Ping => Ok(Type::Ping),
+					Opcode::Pong => Ok(Type::Pong),
+					_ => {
+						check_close_payload(buf)?;
+						Ok(Type::Close)
+					}
+				};
+			}
+			if opcode.is_none() {
+				opcode = Some(frame_opcode);
+				compressed = header.flags.contains(dfh::RSV1);
+			}
+			if header.flags.contains(dfh::FIN) {
+				break;
+			}
+		}
+
+		if compressed {
+			self.decompress_into(buf)?;
+		}
+		if opcode == Some(Opcode::Text) {
+			from_utf8(buf)?;
+			Ok(Type::Text)
+		} else {
+			Ok(Type::Binary)
+		}
+	}
+
+	#[cfg(feature = "deflate")]
+	fn decompresses(&self) -> bool {
+		self.inflater.is_some()
+	}
+
+	#[cfg(not(feature = "deflate"))]
+	fn decompresses(&self) -> bool {
+		false
+	}
+
+	#[cfg(feature = "deflate")]
+	fn decompress_into(&mut self, buf: &mut Vec<u8>) -> WebSocketResult<()> {
+		if let Some(ref mut inflater) = self.inflater {
+			// the compressed payload is moved aside and inflated into `buf`
+			::std::mem::swap(buf, &mut self.compressed);
+			let result = inflater.decompress_into(&self.compressed, buf, self.max_message_size);
+			self.compressed.clear();
+			result?;
+		}
+		Ok(())
+	}
+
+	#[cfg(not(feature = "deflate"))]
+	fn decompress_into(&mut self, _buf: &mut Vec<u8>) -> WebSocketResult<()> {
+		Ok(())
+	}
+
+	/// Reads the header of a data frame and checks it on its own, whatever
+	/// message it belongs to: the opcode must not be reserved, the reserved bits
+	/// and the masking must be as expected, and a control frame must be
+	/// finished and carry at most 125 bytes. Both `recv_message_into` and
+	/// `recv_dataframe` read their frames with this.
+	fn read_frame_header<R: Read>(&self, reader: &mut R) -> WebSocketResult<(FrameHeader, Opcode)> {
+		// `dfh::read_header` rejects unfinished and oversized control frames
+		let header = self.read_header(reader)?;
+		let opcode = match Opcode::new(header.opcode) {
+			Some(opcode @ Opcode::Continuation)
+			| Some(opcode @ Opcode::Text)
+			| Some(opcode @ Opcode::Binary)
+			| Some(opcode @ Opcode::Close)
+			| Some(opcode @ Opcode::Ping)
+			| Some(opcode @ Opcode::Pong) => opcode,
+			// the reserved opcodes have no meaning without an extension
+			_ => return Err(WebSocketError::ProtocolError("Unknown data frame opcode")),
+		};
+		self.check_reserved(&header, opcode == Opcode::Text || opcode == Opcode::Binary)?;
+		if header.mask.is_some() != self.mask {
+			return Err(WebSocketError::DataFrameError(if self.mask {
+				"Expected masked data frame"
+			} else {
+				"Expected unmasked data frame"
+			}));
 		}
+		Ok((header, opcode))
 	}
+
+	/// Fails if the header has reserved bits set, RSV1 is only allowed on the
+	/// first frame of a message while received messages are decompressed.
+	fn check_reserved(&self, header: &FrameHeader, starts_message: bool) -> WebSocketResult<()> {
+		let rsv1 = header.flags.contains(dfh::RSV1);
+		let may_be_compressed = self.decompresses() && starts_message;
+		if header.flags.intersects(dfh::RSV2 | dfh::RSV3) || (rsv1 && !may_be_compressed) {
+			return Err(WebSocketError::ProtocolError(
+				"Unsupported reserved bits received",
+			));
+		}
+		Ok(())
+	}
+
+	/// Reads a data frame, failing if its payload would take the message
+	/// being received over the maximum message size.
+	fn recv_limited_dataframe<R>(&mut self, reader: &mut R) -> WebSocketResult<DataFrame>
+	where
+		R: Read,
+	{
+		let (header, _) = self.read_frame_header(reader)?;
+		let max = match self.max_message_size {
+			Some(max) => max as u64,
+			None => return DataFrame::read_dataframe_payload(reader, header, self.mask),
+		};
+
+		// control frames can be interleaved but are not part of the message
+		let buffered: usize = if header.opcode < 8 {
+			self.buffer.iter().map(|f| f.data.len()).sum()
+		} else {
+			0
+		};
+		if buffered as u64 + header.len > max {
+			self.buffer.clear();
+			return Err(WebSocketError::MessageTooBig);
+		}
+		DataFrame::read_dataframe_payload(reader, header, self.mask)
+	}
+
+	/// Decompress received messages which have the first reserved bit set,
+	/// or stop decompressing them when given `None`.
+	///
+	/// This should only be enabled once the permessage-deflate extension
+	/// has been negotiated with the remote endpoint.
+	#[cfg(feature = "deflate")]
+	pub fn set_inflater(&mut self, inflater: Option<Inflater>) {
+		self.inflater = inflater;
+	}
+
+	#[cfg(feature = "deflate")]
+	fn inflate(&mut self, frames: Vec<DataFrame>) -> WebSocketResult<Vec<DataFrame>> {
+		let inflater = match self.inflater {
+			Some(ref mut inflater)
+				if frames[0].reserved[0]
+					&& (frames[0].opcode == Opcode::Text || frames[0].opcode == Opcode::Binary) =>
+			{
+				inflater
+			}
+			_ => return Ok(frames),
+		};
+
+		let opcode = frames[0].opcode;
+		let mut reserved = frames[0].reserved;
+		let payload: Vec<u8> = frames.into_iter().flat_map(|f| f.data).collect();
+		let payload = inflater.decompress(&payload, self.max_message_size)?;
+		let mut frame = DataFrame::new(true, opcode, payload);
+		// leave any other reserved bits for the message to reject
+		reserved[0] = false;
+		frame.reserved = reserved;
+		Ok(vec![frame])
+	}
+
+	#[cfg(not(feature = "deflate"))]
+	fn inflate(&mut self, frames: Vec<DataFrame>) -> WebSocketResult<Vec<DataFrame>> {
+		Ok(frames)
+	}
+}
+
+/// Fails unless `payload` is empty or a status code that may be sent in a
+/// close message followed by a UTF-8 reason.
+fn check_close_payload(payload: &[u8]) -> WebSocketResult<()> {
+	if payload.is_empty() {
+		return Ok(());
+	}
+	if payload.len() < 2 {
+		return Err(WebSocketError::ProtocolError("Invalid close payload"));
+	}
+	match u16::from(payload[0]) << 8 | u16::from(payload[1]) {
+		1000..=1003 | 1007..=1014 | 3000..=4999 => {}
+		_ => return Err(WebSocketError::ProtocolError("Invalid close status code")),
+	}
+	from_utf8(&payload[2..])?;
+	Ok(())
 }
 
 impl ws::Receiver for Receiver {
@@ -91,12 +626,37 @@ impl ws::Receiver for Receiver {
 
 	type M = OwnedMessage;
 
+	/// Reads a single message, see `recv_message_into`.
+	fn recv_message<R>(&mut self, reader: &mut R) -> WebSocketResult<OwnedMessage>
+	where
+		R: Read,
+	{
+		let mut buf = Vec::new();
+		let message = match self.recv_message_into(reader, &mut buf)? {
+			Type::Text => OwnedMessage::Text(String::from_utf8(buf).map_err(|e| e.utf8_error())?),
+			Type::Binary => OwnedMessage::Binary(buf),
+			Type::Ping => OwnedMessage::Ping(buf),
+			Type::Pong => OwnedMessage::Pong(buf),
+			Type::Close if buf.is_empty() => OwnedMessage::Close(None),
+			Type::Close => {
+				let status_code = u16::from(buf[0]) << 8 | u16::from(buf[1]);
+				let reason = String::from_utf8(buf.split_off(2)).map_err(|e| e.utf8_error())?;
+				OwnedMessage::Close(Some(CloseData::new(status_code, reason)))
+			}
+		};
+		Ok(message)
+	}
+
 	/// Reads a single data frame from the remote endpoint.
+	///
+	/// A frame with reserved bits set results in a
+	/// `WebSocketError::ProtocolError`, except for RSV1 on the first frame
+	/// of a message once an inflater is set with `set_inflater`.
 	fn recv_dataframe<R>(&mut self, reader: &mut R) -> WebSocketResult<DataFrame>
 	where
 		R: Read,
 	{
-		DataFrame::read_dataframe(reader, self.mask)
+		self.recv_limited_dataframe(reader)
 	}
 
 	/// Returns the data frames that constitute one message.
@@ -126,9 +686,18 @@ impl ws::Receiver for Receiver {
 
 			match next.opcode as u8 {
 				// Continuation opcode
-				0 => self.buffer.push(next),
-				// Control frame
+				0 => {
+					self.buffer.push(next);
+					if let Err(e) = self.check_fragments(self.buffer.len()) {
+						self.buffer.clear();
+						return Err(e);
+					}
+				}
+				// Control frame, a close abandons the fragmented message
 				8...15 => {
+					if next.opcode == Opcode::Close {
+						self.buffer.clear();
+					}
 					return Ok(vec![next]);
 				}
 				// Others
@@ -140,6 +709,7 @@ impl ws::Receiver for Receiver {
 			}
 		}
 
-		Ok(::std::mem::replace(&mut self.buffer, Vec::new()))
+		let frames = ::std::mem::replace(&mut self.buffer, Vec::new());
+		self.inflate(frames)
 	}
 }
@@ -1,6 +1,7 @@
 //! The result type used within Rust-WebSocket
 
 use hyper::Error as HttpError;
+use message::CloseData;
 use server::upgrade::HyperIntoWsError;
 use std::convert::From;
 use std::error::Error;
@@ -61,12 +62,43 @@ pub enum WebSocketError {
 	TlsHandshakeInterruption,
 	/// A UTF-8 error
 	Utf8Error(Utf8Error),
+	/// A received message exceeded the maximum message size
+	MessageTooBig,
+	/// The server did not answer the handshake with `101 Switching Protocols`,
+	/// or answered it with the wrong `Sec-WebSocket-Accept`
+	HandshakeFailed {
+		/// The status code of the response
+		status: u16,
+		/// Why the response was rejected
+		reason: &'static str,
+		/// The start of the response body, if it could be read
+		body_snippet: String,
+	},
+	/// The remote endpoint closed the connection before an operation
+	/// finished, with the status code and reason of its close, if any
+	ConnectionClosed(Option<CloseData>),
+	/// The server answered the handshake with `426 Upgrade Required`, listing
+	/// the versions of the protocol it supports instead of version 13
+	UnsupportedVersion {
+		/// The versions in the `Sec-WebSocket-Version` header of the response
+		offered: Vec<u8>,
+	},
 }
 
 impl fmt::Display for WebSocketError {
 	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
 		fmt.write_str("WebSocketError: ")?;
 		fmt.write_str(self.description())?;
+		if let WebSocketError::HandshakeFailed { status, reason, .. } = *self {
+			write!(fmt, " ({}, status {})", reason, status)?;
+		}
+		if let WebSocketError::ConnectionClosed(Some(ref data)) = *self {
+			write!(fmt, " ({}: {})", data.status_code, data.reason)?;
+		}
+		if let WebSocketError::UnsupportedVersion { ref offered } = *self {
+			let offered: Vec<String> = offered.iter().map(|v| v.to_string()).collect();
+			write!(fmt, " (server supports {})", offered.join(", "))?;
+		}
 		Ok(())
 	}
 }
@@ -90,6 +122,10 @@ impl Error for WebSocketError {
 			WebSocketError::TlsHandshakeInterruption => "TLS Handshake interrupted",
 			WebSocketError::Utf8Error(_) => "UTF-8 failure",
 			WebSocketError::WebSocketUrlError(_) => "WebSocket URL failure",
+			WebSocketError::MessageTooBig => "Message too big",
+			WebSocketError::HandshakeFailed { .. } => "WebSocket handshake failed",
+			WebSocketError::ConnectionClosed(_) => "Connection closed by the remote endpoint",
+			WebSocketError::UnsupportedVersion { .. } => "Unsupported WebSocket version",
 		}
 	}
 
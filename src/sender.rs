@@ -1,36 +1,81 @@
 //! The default implementation of a WebSocket Sender.
 
-use result::WebSocketResult;
+use dataframe::DataFrame as OwnedDataFrame;
+use dataframe::Opcode;
+use dataframe::{FrameDirection, FrameObserver};
+#[cfg(feature = "deflate")]
+use deflate::Deflater;
+use message::Message;
+use result::{WebSocketError, WebSocketResult};
+use std::collections::VecDeque;
 use std::io::Result as IoResult;
 use std::io::Write;
+use std::mem;
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use stream::sync::AsTcpStream;
 pub use stream::sync::Shutdown;
 use ws;
 use ws::dataframe::DataFrame;
 use ws::sender::Sender as SenderTrait;
+use ws::util::header as dfh;
+use ws::util::mask;
+use ws::Message as MessageTrait;
 
 /// A writer that bundles a stream with a serializer to send the messages.
 /// This is used in the client's `.split()` function as the writing component.
 ///
 /// It can also be useful to use a websocket connection without a handshake.
+///
+/// The frames left in the write buffer of the sender are not written when
+/// the writer is dropped, call `flush` before dropping it.
 pub struct Writer<W> {
 	/// The stream that websocket messages will be written to
 	pub stream: W,
 	/// The serializer that will be used to serialize the messages
 	pub sender: Sender,
+	// restored by `Client::from_split`
+	close_sent: bool,
 }
 
 impl<W> Writer<W>
 where
 	W: Write,
 {
+	/// Bundles a stream with a sender, for a connection without a handshake.
+	pub fn new(stream: W, sender: Sender) -> Self {
+		Writer {
+			stream,
+			sender,
+			close_sent: false,
+		}
+	}
+
+	#[doc(hidden)]
+	pub fn with_close_sent(mut self, close_sent: bool) -> Self {
+		self.close_sent = close_sent;
+		self
+	}
+
+	/// Whether a close message was sent, before the split or through this
+	/// writer.
+	pub fn is_close_sent(&self) -> bool {
+		self.close_sent
+	}
+
 	/// Sends a single data frame to the remote endpoint.
 	pub fn send_dataframe<D>(&mut self, dataframe: &D) -> WebSocketResult<()>
 	where
 		D: DataFrame,
 		W: Write,
 	{
-		self.sender.send_dataframe(&mut self.stream, dataframe)
+		self.sender.send_dataframe(&mut self.stream, dataframe)?;
+		if dataframe.opcode() == Opcode::Close as u8 {
+			self.close_sent = true;
+		}
+		Ok(())
 	}
 
 	/// Sends a single message to the remote endpoint.
@@ -38,7 +83,70 @@ where
 	where
 		M: ws::Message,
 	{
-		self.sender.send_message(&mut self.stream, message)
+		self.sender.send_message(&mut self.stream, message)?;
+		if message.is_close() {
+			self.close_sent = true;
+		}
+		Ok(())
+	}
+
+	/// Sends many messages to the remote endpoint with a single write.
+	/// See `Sender::send_messages`.
+	pub fn send_messages<'m, I, M>(&mut self, messages: I) -> WebSocketResult<()>
+	where
+		I: IntoIterator<Item = &'m M>,
+		M: 'm + ws::Message,
+	{
+		let messages: Vec<&M> = messages.into_iter().collect();
+		self.sender
+			.send_messages(&mut self.stream, messages.iter().cloned())?;
+		if messages.iter().any(|message| message.is_close()) {
+			self.close_sent = true;
+		}
+		Ok(())
+	}
+
+	/// Writes the frames held in the write buffer of the sender, if any, and
+	/// flushes the stream.
+	pub fn flush(&mut self) -> WebSocketResult<()> {
+		self.sender.flush(&mut self.stream)
+	}
+}
+
+impl Writer<TcpStream> {
+	/// Sends an empty `Ping` every `interval` from a new thread, to keep an
+	/// idle connection from being closed by intermediaries.
+	///
+	/// The thread stops once this writer is dropped or a ping cannot be sent,
+	/// for example because the connection was closed or shut down. Pings and
+	/// messages sent through this writer are never interleaved.
+	///
+	/// Only pings are sent, the reader side of the connection is responsible
+	/// for consuming the pongs that the remote endpoint sends back.
+	pub fn spawn_keepalive(&mut self, interval: Duration) -> IoResult<JoinHandle<()>> {
+		let mut stream = self.stream.try_clone()?;
+		let lock = self
+			.sender
+			.keepalive
+			.get_or_insert_with(|| Arc::new(Mutex::new(())));
+		let lock = Arc::downgrade(lock);
+		let mask = self.sender.mask;
+		let ping_times = self.sender.ping_times.clone();
+
+		Ok(thread::spawn(move || loop {
+			thread::sleep(interval);
+			let lock = match lock.upgrade() {
+				Some(lock) => lock,
+				None => return,
+			};
+			let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+			if Message::ping(&[][..]).serialize(&mut stream, mask).is_err() {
+				return;
+			}
+			if let Some(ref ping_times) = ping_times {
+				ping_times.sent(&[]);
+			}
+		}))
 	}
 }
 
@@ -52,6 +160,11 @@ where
 		self.stream.as_tcp().shutdown(Shutdown::Write)
 	}
 
+	/// Sets the write timeout of the stream, see `Client::set_write_timeout`.
+	pub fn set_write_timeout(&self, dur: Option<Duration>) -> IoResult<()> {
+		self.stream.as_tcp().set_write_timeout(dur)
+	}
+
 	/// Shuts down both Sender and Receiver, will cause all pending and future IO to
 	/// return immediately with an appropriate value.
 	pub fn shutdown_all(&self) -> IoResult<()> {
@@ -59,21 +172,578 @@ where
 	}
 }
 
+/// The most pings kept by `PingTimes`, older ones are forgotten.
+const MAX_PING_TIMES: usize = 16;
+
+/// The times at which the latest pings were sent, shared by the `Writer`
+/// and the `Reader` of a split client so that the reader can tell the
+/// round trip time of the pongs it receives.
+///
+/// Only the last 16 pings are kept. A pong is matched to the oldest ping
+/// with the same payload, which is then forgotten.
+#[derive(Clone, Debug, Default)]
+pub struct PingTimes(Arc<Mutex<SentPings>>);
+
+/// The payloads of the pings sent and when they were sent, oldest first.
+type SentPings = VecDeque<(Vec<u8>, Instant)>;
+
+impl PingTimes {
+	/// Creates an empty table of pings.
+	pub fn new() -> Self {
+		PingTimes::default()
+	}
+
+	/// Remembers that a ping with the given payload was just sent.
+	pub fn sent(&self, payload: &[u8]) {
+		let mut pings = self.0.lock().unwrap_or_else(|e| e.into_inner());
+		if pings.len() == MAX_PING_TIMES {
+			pings.pop_front();
+		}
+		pings.push_back((payload.to_vec(), Instant::now()));
+	}
+
+	/// The time since the ping answered by a pong with the given payload
+	/// was sent, if it is known.
+	pub fn pong_received(&self, payload: &[u8]) -> Option<Duration> {
+		let mut pings = self.0.lock().unwrap_or_else(|e| e.into_inner());
+		let position = pings.iter().position(|ping| ping.0[..] == *payload)?;
+		pings.remove(position).map(|(_, sent)| sent.elapsed())
+	}
+}
+
 /// A Sender that wraps a Writer and provides a default implementation using
 /// DataFrames and Messages.
 pub struct Sender {
 	mask: bool,
+	#[cfg(feature = "deflate")]
+	deflater: Option<Deflater>,
+	keepalive: Option<Arc<Mutex<()>>>,
+	mask_key: Option<[u8; 4]>,
+	write_buffer_size: usize,
+	write_buffer: Vec<u8>,
+	observer: Option<FrameObserver>,
+	ping_times: Option<PingTimes>,
+	// pings not yet written to the stream, recorded in `ping_times` once they are
+	unsent_pings: Vec<Vec<u8>>,
 }
 
 impl Sender {
 	/// Create a new WebSocketSender using the specified Writer.
 	pub fn new(mask: bool) -> Sender {
-		Sender { mask }
+		Sender {
+			mask,
+			#[cfg(feature = "deflate")]
+			deflater: None,
+			keepalive: None,
+			mask_key: None,
+			write_buffer_size: 0,
+			write_buffer: Vec::new(),
+			observer: None,
+			ping_times: None,
+			unsent_pings: Vec::new(),
+		}
+	}
+
+	/// Remembers when every ping is sent in `ping_times`, or stops doing so
+	/// when given `None`. See `Reader::incoming_messages_with_rtt`.
+	///
+	/// A ping counts as sent once it is written to the stream, so a ping held
+	/// in the write buffer is only recorded when the buffer is written.
+	pub fn set_ping_times(&mut self, ping_times: Option<PingTimes>) {
+		self.ping_times = ping_times;
+	}
+
+	/// Calls `observer` with the header of every data frame sent, or stops
+	/// calling it when given `None`. Pings sent by the keepalive thread of
+	/// `Writer::spawn_keepalive` are not shown to it.
+	pub fn set_frame_observer(&mut self, observer: Option<FrameObserver>) {
+		self.observer = observer;
+	}
+
+	/// Keep the frames sent in a buffer until it holds at least `size` bytes,
+	/// so that many small messages are written to the stream together, or
+	/// write every frame as soon as it is sent when given 0.
+	///
+	/// Each message is still framed on its own. Frames left in the buffer are
+	/// only written by `flush` or by the send that fills the buffer, so the
+	/// buffer should be flushed before waiting for an answer and before the
+	/// stream is dropped, which a `Client` or `Writer` does not do on its
+	/// own. Frames written by the keepalive thread of
+	/// `Writer::spawn_keepalive` are never buffered.
+	pub fn set_write_buffer(&mut self, size: usize) {
+		self.write_buffer_size = size;
+		self.write_buffer.reserve(size);
+	}
+
+	/// Frames all the messages into one buffer and writes it to the writer
+	/// at once, instead of writing every message on its own.
+	///
+	/// All the messages are checked before any of them is framed, so if one
+	/// of them cannot be framed, for example a ping with a payload longer
+	/// than 125 bytes, none of the messages are sent, nothing is compressed
+	/// and its error is returned. With a write buffer enabled the frames are
+	/// added to it as usual.
+	pub fn send_messages<'m, I, M, W>(&mut self, writer: &mut W, messages: I) -> WebSocketResult<()>
+	where
+		I: IntoIterator<Item = &'m M>,
+		M: 'm + ws::Message,
+		W: Write,
+	{
+		let messages: Vec<&M> = messages.into_iter().collect();
+		for message in &messages {
+			check_framing(*message)?;
+		}
+
+		let lock = self.write_lock();
+		let _guard = lock
+			.as_ref()
+			.map(|l| l.lock().unwrap_or_else(|e| e.into_inner()));
+		let mut data = Vec::new();
+		for message in messages {
+			self.write_message(&mut data, message)?;
+		}
+		self.buffer(writer, |_, writer| {
+			writer.write_all(&data)?;
+			Ok(())
+		})
+	}
+
+	/// Writes the frames held in the write buffer, if any, and flushes the
+	/// writer.
+	pub fn flush<W: Write>(&mut self, writer: &mut W) -> WebSocketResult<()> {
+		let lock = self.write_lock();
+		let _guard = lock
+			.as_ref()
+			.map(|l| l.lock().unwrap_or_else(|e| e.into_inner()));
+		self.write_buffered(writer)?;
+		writer.flush()?;
+		Ok(())
+	}
+
+	fn write_buffered<W: Write>(&mut self, writer: &mut W) -> WebSocketResult<()> {
+		if !self.write_buffer.is_empty() {
+			writer.write_all(&self.write_buffer)?;
+			self.write_buffer.clear();
+			self.pings_written();
+		}
+		Ok(())
+	}
+
+	/// Records the pings that were just written to the stream.
+	fn pings_written(&mut self) {
+		if let Some(ref ping_times) = self.ping_times {
+			for payload in &self.unsent_pings {
+				ping_times.sent(payload);
+			}
+		}
+		self.unsent_pings.clear();
+	}
+
+	/// Writes through the write buffer, if one is enabled, showing the
+	/// frames written to the frame observer.
+	fn buffer<W, F>(&mut self, writer: &mut W, write: F) -> WebSocketResult<()>
+	where
+		W: Write,
+		F: FnOnce(&mut Sender, &mut dyn Write) -> WebSocketResult<()>,
+	{
+		let observer = match self.observer.clone() {
+			Some(observer) => observer,
+			None => return self.write_through(writer, write),
+		};
+
+		let unsent = self.unsent_pings.len();
+		let mut data = Vec::new();
+		if let Err(e) = write(self, &mut data) {
+			self.unsent_pings.truncate(unsent);
+			return Err(e);
+		}
+		{
+			let mut observer = observer.lock().unwrap_or_else(|e| e.into_inner());
+			let mut frames = &data[..];
+			while !frames.is_empty() {
+				let header = dfh::read_header(&mut frames)?;
+				(*observer)(FrameDirection::Sent, &header);
+				frames = &frames[header.len as usize..];
+			}
+		}
+		self.write_through(writer, |_, writer| {
+			writer.write_all(&data)?;
+			Ok(())
+		})
+	}
+
+	fn write_through<W, F>(&mut self, writer: &mut W, write: F) -> WebSocketResult<()>
+	where
+		W: Write,
+		F: FnOnce(&mut Sender, &mut dyn Write) -> WebSocketResult<()>,
+	{
+		if self.write_buffer_size == 0 && self.write_buffer.is_empty() {
+			// nothing is buffered, so all the unsent pings are written by `write`
+			let result = write(self, writer);
+			match result {
+				Ok(()) => self.pings_written(),
+				Err(_) => self.unsent_pings.clear(),
+			}
+			return result;
+		}
+
+		let unsent = self.unsent_pings.len();
+		let mut buffer = mem::take(&mut self.write_buffer);
+		let result = write(self, &mut buffer);
+		self.write_buffer = buffer;
+		if result.is_err() {
+			self.unsent_pings.truncate(unsent);
+		}
+		result?;
+		if self.write_buffer.len() >= self.write_buffer_size {
+			self.write_buffered(writer)?;
+		}
+		Ok(())
+	}
+
+	/// Sets whether the frames sent are masked.
+	///
+	/// RFC6455 requires that clients mask every frame they send and that
+	/// servers never mask theirs.
+	pub fn set_mask(&mut self, mask: bool) {
+		self.mask = mask;
+	}
+
+	/// Mask every frame with the given masking key instead of a new random
+	/// key for each frame, or go back to random keys when given `None`.
+	///
+	/// **A fixed masking key is insecure**, the keys must be unpredictable to
+	/// protect intermediaries from cache poisoning, so this is only meant for
+	/// tests and conformance tools. It has no effect if this sender does
+	/// not mask its frames.
+	pub fn set_mask_key(&mut self, mask_key: Option<[u8; 4]>) {
+		self.mask_key = mask_key;
+	}
+
+	/// The masking key for the next frame, if frames are masked.
+	fn masking_key(&self) -> Option<[u8; 4]> {
+		if !self.mask {
+			return None;
+		}
+		Some(self.mask_key.unwrap_or_else(mask::gen_mask))
+	}
+
+	/// Compress the payload of every text and binary message sent, or stop
+	/// compressing them when given `None`.
+	///
+	/// This should only be enabled once the permessage-deflate extension
+	/// has been negotiated with the remote endpoint.
+	#[cfg(feature = "deflate")]
+	pub fn set_deflater(&mut self, deflater: Option<Deflater>) {
+		self.deflater = deflater;
+	}
+
+	/// The lock shared with the keepalive thread, if any, so that pings are
+	/// never written in the middle of another frame.
+	fn write_lock(&self) -> Option<Arc<Mutex<()>>> {
+		self.keepalive.clone()
+	}
+
+	fn write_message<M, W>(&mut self, writer: &mut W, message: &M) -> WebSocketResult<()>
+	where
+		M: ws::Message,
+		W: Write,
+	{
+		if let (true, Some(payload)) = (self.ping_times.is_some(), message.ping_payload()) {
+			self.unsent_pings.push(payload.to_vec());
+		}
+
+		#[cfg(feature = "deflate")]
+		{
+			if self.deflater.is_some() {
+				return self.write_compressed(writer, message);
+			}
+		}
+
+		if self.mask_key.is_none() {
+			message.serialize(writer, self.mask)?;
+			return Ok(());
+		}
+		for frame in &dataframes(message)? {
+			frame.write_to_with_key(writer, self.masking_key())?;
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "deflate")]
+	fn write_compressed<M, W>(&mut self, writer: &mut W, message: &M) -> WebSocketResult<()>
+	where
+		M: ws::Message,
+		W: Write,
+	{
+		let frames = dataframes(message)?;
+		match frames.first().map(|f| f.opcode) {
+			Some(opcode @ Opcode::Text) | Some(opcode @ Opcode::Binary) => {
+				let payload: Vec<u8> = frames.into_iter().flat_map(|f| f.data).collect();
+				let payload = match self.deflater {
+					Some(ref mut deflater) => deflater.compress(&payload)?,
+					None => payload,
+				};
+				let mut frame = OwnedDataFrame::new(true, opcode, payload);
+				frame.reserved[0] = true;
+				frame.write_to_with_key(writer, self.masking_key())?;
+			}
+			_ => {
+				for frame in &frames {
+					frame.write_to_with_key(writer, self.masking_key())?;
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Fails if the message cannot be framed. Only control frames can fail,
+/// as their payload may be at most 125 bytes long, so data messages are
+/// never looked at.
+fn check_framing<M: ws::Message>(message: &M) -> WebSocketResult<()> {
+	match message.control_size() {
+		Some(size) if size > 125 => Err(WebSocketError::ProtocolError(if message.is_close() {
+			"Close reason too long, it can be at most 123 bytes"
+		} else {
+			"Control frame payload too long, it can be at most 125 bytes"
+		})),
+		_ => Ok(()),
 	}
 }
 
+/// Serializes a message unmasked, then reads back the frames it is made of.
+fn dataframes<M: ws::Message>(message: &M) -> WebSocketResult<Vec<OwnedDataFrame>> {
+	let mut data = Vec::with_capacity(message.message_size(false));
+	message.serialize(&mut data, false)?;
+	let mut reader = &data[..];
+	let mut frames = Vec::new();
+	while !reader.is_empty() {
+		frames.push(OwnedDataFrame::read_dataframe(&mut reader, false)?);
+	}
+	Ok(frames)
+}
+
 impl ws::Sender for Sender {
 	fn is_masked(&self) -> bool {
 		self.mask
 	}
+
+	fn send_dataframe<D, W>(&mut self, writer: &mut W, dataframe: &D) -> WebSocketResult<()>
+	where
+		D: DataFrame,
+		W: Write,
+	{
+		let lock = self.write_lock();
+		let _guard = lock
+			.as_ref()
+			.map(|l| l.lock().unwrap_or_else(|e| e.into_inner()));
+		self.buffer(writer, |sender, writer| {
+			dataframe.write_to_with_key(writer, sender.masking_key())
+		})
+	}
+
+	fn send_message<M, W>(&mut self, writer: &mut W, message: &M) -> WebSocketResult<()>
+	where
+		M: ws::Message,
+		W: Write,
+	{
+		let lock = self.write_lock();
+		let _guard = lock
+			.as_ref()
+			.map(|l| l.lock().unwrap_or_else(|e| e.into_inner()));
+		self.buffer(writer, |sender, mut writer| {
+			sender.write_message(&mut writer, message)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use dataframe::{DataFrame, Opcode};
+	use message::{CloseData, OwnedMessage};
+	use std::io;
+	use std::net::TcpListener;
+	use std::time::Instant;
+
+	#[test]
+	fn keepalive_sends_pings() {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+		let (mut server, _) = listener.accept().unwrap();
+
+		let mut writer = Writer::new(stream, Sender::new(true));
+		let keepalive = writer.spawn_keepalive(Duration::from_millis(50)).unwrap();
+
+		server
+			.set_read_timeout(Some(Duration::from_millis(50)))
+			.unwrap();
+		let start = Instant::now();
+		let mut pings = 0;
+		while start.elapsed() < Duration::from_millis(250) {
+			if let Ok(frame) = DataFrame::read_dataframe(&mut server, true) {
+				assert_eq!(frame.opcode, Opcode::Ping);
+				pings += 1;
+			}
+		}
+		assert!(pings >= 4, "only {} pings received", pings);
+
+		// the thread stops once the writer is gone
+		drop(writer);
+		keepalive.join().unwrap();
+	}
+
+	#[test]
+	#[cfg(feature = "deflate")]
+	fn failed_batch_leaves_compression_untouched() {
+		use deflate::DeflateConfig;
+		use receiver::Receiver;
+		use ws::Receiver as ReceiverTrait;
+
+		let config = DeflateConfig::default();
+		let mut sender = Sender::new(false);
+		sender.set_deflater(Some(config.deflater(false).unwrap()));
+		let ping_times = PingTimes::new();
+		sender.set_ping_times(Some(ping_times.clone()));
+
+		let mut written = Vec::new();
+		let batch = vec![
+			Message::text("compressed first"),
+			Message::ping(b"pending".to_vec()),
+			Message::ping(vec![0; 200]),
+		];
+		match sender.send_messages(&mut written, &batch) {
+			Err(WebSocketError::ProtocolError(_)) => {}
+			other => panic!("unexpected result {:?}", other),
+		}
+		assert!(written.is_empty());
+		assert_eq!(ping_times.pong_received(b"pending"), None);
+
+		sender
+			.send_message(&mut written, &Message::text("compressed first"))
+			.unwrap();
+		let mut receiver = Receiver::new(false);
+		receiver.set_inflater(Some(config.inflater(true)));
+		assert_eq!(
+			receiver.recv_message(&mut &written[..]).unwrap(),
+			OwnedMessage::Text("compressed first".to_string())
+		);
+	}
+
+	#[test]
+	fn pings_recorded_once_written() {
+		struct Broken;
+		impl Write for Broken {
+			fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+				Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken"))
+			}
+			fn flush(&mut self) -> io::Result<()> {
+				Ok(())
+			}
+		}
+
+		let ping_times = PingTimes::new();
+		let mut sender = Sender::new(false);
+		sender.set_ping_times(Some(ping_times.clone()));
+		assert!(sender
+			.send_message(&mut Broken, &Message::ping(b"lost".to_vec()))
+			.is_err());
+		assert_eq!(ping_times.pong_received(b"lost"), None);
+
+		sender.set_write_buffer(1024);
+		let mut written = Vec::new();
+		sender
+			.send_message(&mut written, &Message::ping(b"buffered".to_vec()))
+			.unwrap();
+		assert_eq!(ping_times.pong_received(b"buffered"), None);
+		sender.flush(&mut written).unwrap();
+		assert!(ping_times.pong_received(b"buffered").is_some());
+	}
+
+	#[test]
+	fn batch_serializes_data_messages_once() {
+		use std::cell::Cell;
+		use ws::dataframe::DataFrame as DataFrameTrait;
+
+		struct Counted(OwnedMessage, Cell<usize>);
+		impl ws::Message for Counted {
+			fn serialize(&self, writer: &mut dyn Write, masked: bool) -> WebSocketResult<()> {
+				self.1.set(self.1.get() + 1);
+				self.0.write_to(writer, masked)
+			}
+			fn message_size(&self, masked: bool) -> usize {
+				self.0.message_size(masked)
+			}
+			fn from_dataframes<D: DataFrameTrait>(frames: Vec<D>) -> WebSocketResult<Self> {
+				Ok(Counted(
+					OwnedMessage::from_dataframes(frames)?,
+					Cell::new(0),
+				))
+			}
+		}
+
+		let large = Counted(OwnedMessage::Binary(vec![0; 64 * 1024]), Cell::new(0));
+		let mut written = Vec::new();
+		Sender::new(false)
+			.send_messages(&mut written, vec![&large])
+			.unwrap();
+		assert_eq!(large.1.get(), 1);
+	}
+
+	#[test]
+	fn fixed_mask_key() {
+		let key = [0x37, 0xfa, 0x21, 0x3d];
+		let mut sender = Sender::new(true);
+		sender.set_mask_key(Some(key));
+
+		let mut written = Vec::new();
+		sender
+			.send_message(&mut written, &Message::text("Hello"))
+			.unwrap();
+		// the masked "Hello" example of RFC6455 section 5.7
+		assert_eq!(
+			written,
+			vec![0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]
+		);
+
+		written.clear();
+		sender
+			.send_dataframe(
+				&mut written,
+				&DataFrame::new(true, Opcode::Binary, vec![0; 3]),
+			)
+			.unwrap();
+		assert_eq!(
+			written,
+			vec![0x82, 0x83, 0x37, 0xfa, 0x21, 0x3d, 0x37, 0xfa, 0x21]
+		);
+
+		sender.set_mask_key(None);
+		written.clear();
+		sender
+			.send_message(&mut written, &Message::text("Hello"))
+			.unwrap();
+		let frame = DataFrame::read_dataframe(&mut &written[..], true).unwrap();
+		assert_eq!(frame.data, b"Hello");
+	}
+
+	#[test]
+	fn close_reason_length() {
+		let mut sender = Sender::new(true);
+		let mut written = Vec::new();
+
+		let close = OwnedMessage::Close(Some(CloseData::new(1000, "a".repeat(200))));
+		match sender.send_message(&mut written, &close) {
+			Err(WebSocketError::ProtocolError(_)) => {}
+			other => panic!("unexpected result {:?}", other),
+		}
+		assert!(written.is_empty());
+
+		let close = OwnedMessage::Close(Some(CloseData::new(1000, "a".repeat(123))));
+		sender.send_message(&mut written, &close).unwrap();
+		let frame = DataFrame::read_dataframe(&mut &written[..], true).unwrap();
+		assert_eq!(frame.opcode, Opcode::Close);
+		assert_eq!(frame.data.len(), 125);
+	}
 }
@@ -292,7 +292,7 @@ mod tests {
 			Ok(_) => panic!("expected error"),
 			Err(e) => match e.error {
 				HyperIntoWsError::Io(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
-				_ => panic!("unexpected error {}"),
+				ref e => panic!("unexpected error {}", e),
 			},
 		}
 	}
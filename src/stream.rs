@@ -108,6 +108,7 @@ pub mod sync {
 	pub use std::net::Shutdown;
 	pub use std::net::TcpStream;
 	use std::ops::Deref;
+	use std::sync::Arc;
 
 	pub use super::Stream;
 
@@ -123,6 +124,8 @@ pub mod sync {
 	/// up the client into two parts.
 	///
 	/// Notice however that this is not possible to do with SSL.
+	/// Other streams that can be shared between a reader and a writer can be
+	/// split by wrapping them in a `SharedStream`.
 	pub trait Splittable {
 		/// The reading component of this type
 		type Reader: Read;
@@ -155,11 +158,66 @@ pub mod sync {
 		}
 	}
 
+	/// A stream shared through an `Arc`, for streams that are read from and
+	/// written to through shared references, like `&TcpStream` or
+	/// `&UnixStream`. Splitting it gives two handles to the same stream.
+	///
+	/// The stream must support a read and a write happening at the same time
+	/// from different threads, and must keep the bytes of each direction
+	/// apart, so that reading never interferes with writing and the other
+	/// way around. This holds for sockets, but not for SSL streams which
+	/// share their state between both directions, or for types whose
+	/// `Read` and `Write` impls for `&T` simply lock the whole stream, which
+	/// would block the writer for as long as the reader waits for data.
+	pub struct SharedStream<T>(pub Arc<T>);
+
+	impl<T> Read for SharedStream<T>
+	where
+		for<'a> &'a T: Read,
+	{
+		#[inline(always)]
+		fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+			(&*self.0).read(buf)
+		}
+	}
+
+	impl<T> Write for SharedStream<T>
+	where
+		for<'a> &'a T: Write,
+	{
+		#[inline(always)]
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			(&*self.0).write(buf)
+		}
+		#[inline(always)]
+		fn flush(&mut self) -> io::Result<()> {
+			(&*self.0).flush()
+		}
+	}
+
+	impl<T> Splittable for SharedStream<T>
+	where
+		for<'a> &'a T: Read + Write,
+	{
+		type Reader = SharedStream<T>;
+		type Writer = SharedStream<T>;
+
+		fn split(self) -> io::Result<(SharedStream<T>, SharedStream<T>)> {
+			Ok((SharedStream(self.0.clone()), self))
+		}
+	}
+
 	/// The ability access a borrow to an underlying TcpStream,
 	/// so one can set options on the stream such as `nonblocking`.
 	pub trait AsTcpStream {
 		/// Get a borrow of the TcpStream
 		fn as_tcp(&self) -> &TcpStream;
+
+		/// Get the DER-encoded certificate of the peer, if this is a TLS
+		/// stream and the peer sent a certificate
+		fn peer_certificate(&self) -> Option<Vec<u8>> {
+			None
+		}
 	}
 
 	impl AsTcpStream for TcpStream {
@@ -173,6 +231,13 @@ pub mod sync {
 		fn as_tcp(&self) -> &TcpStream {
 			self.get_ref()
 		}
+
+		fn peer_certificate(&self) -> Option<Vec<u8>> {
+			match TlsStream::peer_certificate(self) {
+				Ok(Some(certificate)) => certificate.to_der().ok(),
+				_ => None,
+			}
+		}
 	}
 
 	impl<T> AsTcpStream for Box<T>
@@ -182,5 +247,9 @@ pub mod sync {
 		fn as_tcp(&self) -> &TcpStream {
 			self.deref().as_tcp()
 		}
+
+		fn peer_certificate(&self) -> Option<Vec<u8>> {
+			self.deref().peer_certificate()
+		}
 	}
 }
@@ -2,7 +2,7 @@
 //! that all dataframes should share. This is so one can
 //! optimize the memory footprint of a dataframe for their
 //! own needs, and be able to use custom dataframes quickly
-use result::WebSocketResult;
+use result::{WebSocketError, WebSocketResult};
 use std::io::Write;
 use ws::util::header as dfh;
 use ws::util::mask;
@@ -51,6 +51,28 @@ pub trait DataFrame {
 
 	/// Writes a DataFrame to a Writer.
 	fn write_to(&self, writer: &mut Write, mask: bool) -> WebSocketResult<()> {
+		let masking_key = if mask { Some(mask::gen_mask()) } else { None };
+		self.write_to_with_key(writer, masking_key)
+	}
+
+	/// Writes a DataFrame to a Writer, masked with the given masking key
+	/// or unmasked if there is none.
+	///
+	/// Control frames with a payload longer than 125 bytes are invalid, they
+	/// result in a `WebSocketError::ProtocolError` and nothing is written.
+	fn write_to_with_key(
+		&self,
+		writer: &mut dyn Write,
+		masking_key: Option<[u8; 4]>,
+	) -> WebSocketResult<()> {
+		if self.opcode() >= 8 && self.size() > 125 {
+			return Err(WebSocketError::ProtocolError(if self.opcode() == 8 {
+				"Close reason too long, it can be at most 123 bytes"
+			} else {
+				"Control frame payload too long, it can be at most 125 bytes"
+			}));
+		}
+
 		let mut flags = dfh::DataFrameFlags::empty();
 		if self.is_last() {
 			flags.insert(dfh::FIN);
@@ -68,8 +90,6 @@ pub trait DataFrame {
 			}
 		}
 
-		let masking_key = if mask { Some(mask::gen_mask()) } else { None };
-
 		let header = dfh::DataFrameHeader {
 			flags,
 			opcode: self.opcode() as u8,
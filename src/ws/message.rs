@@ -16,4 +16,25 @@ pub trait Message: Sized {
 
 	/// Attempt to form a message from a series of data frames
 	fn from_dataframes<D: DataFrameable>(frames: Vec<D>) -> WebSocketResult<Self>;
+
+	/// Checks if this is a close message, which lets a client keep track
+	/// of the closing handshake. Message types which cannot be a close
+	/// message can keep the default.
+	fn is_close(&self) -> bool {
+		false
+	}
+
+	/// The payload of this message if it is a ping, which lets a sender
+	/// keep track of the pings waiting for their pong. Message types which
+	/// cannot be a ping can keep the default.
+	fn ping_payload(&self) -> Option<&[u8]> {
+		None
+	}
+
+	/// The size of the payload of this message if it is a control message,
+	/// which lets a sender reject an oversized one before writing anything.
+	/// Message types which cannot be a control message can keep the default.
+	fn control_size(&self) -> Option<usize> {
+		None
+	}
 }